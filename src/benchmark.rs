@@ -0,0 +1,234 @@
+use std::fmt;
+use std::fmt::Display;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::solution::{solution, SolveError};
+
+// differences smaller than this are noise, whatever the ratio
+const MIN_SIGNIFICANT_MS: f64 = 1.0;
+
+// the parse phase is not measured for the days whose parsing is not separated from solving
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Timing {
+    pub day: usize,
+    pub parse_ms: Option<f64>,
+    pub part1_ms: f64,
+    pub part2_ms: f64,
+}
+
+impl Timing {
+    pub fn phases(&self) -> [(&'static str, Option<f64>); 3] {
+        [("parse", self.parse_ms), ("part1", Some(self.part1_ms)), ("part2", Some(self.part2_ms))]
+    }
+}
+
+// an empty field when the phase is not measured
+fn format_ms(ms: Option<f64>) -> String {
+    ms.map_or(String::new(), |ms| format!("{:.3}", ms))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    pub timings: Vec<Timing>,
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(raw: &str) -> Result<Report, String> {
+        serde_json::from_str(raw).map_err(|e| format!("Invalid JSON report: {}", e))
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("day,parse_ms,part1_ms,part2_ms\n");
+        for t in &self.timings {
+            csv.push_str(&format!("{},{},{:.3},{:.3}\n", t.day, format_ms(t.parse_ms), t.part1_ms, t.part2_ms));
+        }
+        csv
+    }
+
+    pub fn from_csv(raw: &str) -> Result<Report, String> {
+        let timings = raw.lines()
+            .skip(1)
+            .filter(|l| !l.is_empty())
+            .enumerate()
+            .map(|(index, line)| {
+                let values = line.split(',')
+                    .map(|v| Some(v.trim()).filter(|v| !v.is_empty()).map(str::parse::<f64>).transpose())
+                    .collect::<Result<Vec<_>, _>>();
+                match values.as_deref() {
+                    Ok(&[Some(day), parse_ms, Some(part1_ms), Some(part2_ms)]) => Ok(Timing { day: day as usize, parse_ms, part1_ms, part2_ms }),
+                    _ => Err(format!("Invalid CSV report at line {}: `{}`", index + 2, line))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Report { timings })
+    }
+
+    pub fn timing(&self, day: usize) -> Option<&Timing> {
+        self.timings.iter().find(|t| t.day == day)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>3} {:>12} {:>12} {:>12}", "day", "parse ms", "part1 ms", "part2 ms")?;
+        for t in &self.timings {
+            let parse_ms = t.parse_ms.map_or("-".to_string(), |ms| format!("{:.3}", ms));
+            writeln!(f, "{:>3} {:>12} {:>12.3} {:>12.3}", t.day, parse_ms, t.part1_ms, t.part2_ms)?;
+        }
+        Ok(())
+    }
+}
+
+// every phase runs `runs` times and the fastest run is kept
+pub fn measure(day: usize, input: &str, runs: usize) -> Result<Timing, SolveError> {
+    let solution = solution(day).ok_or_else(|| SolveError::unsolvable(day, "days go from 1 to 25"))?;
+    let parse_ms = match solution.parse(input) {
+        Some(_) => Some(fastest(runs, || solution.parse(input).unwrap_or(Ok(())))?),
+        None => None,
+    };
+    let part1_ms = fastest(runs, || solution.part1(input))?;
+    let part2_ms = fastest(runs, || solution.part2(input))?;
+    Ok(Timing { day, parse_ms, part1_ms, part2_ms })
+}
+
+// the quickest of `runs` runs of the phase, in milliseconds
+pub fn fastest<T, E>(runs: usize, phase: impl Fn() -> Result<T, E>) -> Result<f64, E> {
+    let mut best = Duration::MAX;
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        black_box(phase()?);
+        best = best.min(start.elapsed());
+    }
+    Ok(best.as_secs_f64() * 1000.0)
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Trend {
+    Faster,
+    Slower,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub day: usize,
+    pub phase: &'static str,
+    pub baseline_ms: f64,
+    pub current_ms: f64,
+    pub trend: Trend,
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ratio = if self.baseline_ms == 0.0 { 0.0 } else { (self.current_ms / self.baseline_ms - 1.0) * 100.0 };
+        let trend = match self.trend {
+            Trend::Faster => "faster",
+            Trend::Slower => "SLOWER",
+            Trend::Unchanged => "unchanged",
+        };
+        write!(f, "day {:>2} {}: {:.3} ms -> {:.3} ms ({:+.1}%, {})", self.day, self.phase, self.baseline_ms, self.current_ms, ratio, trend)
+    }
+}
+
+// `tolerance` is the relative difference accepted as noise, e.g. 0.1 for 10%.
+// Phases not measured in either report are skipped.
+pub fn compare(baseline: &Report, current: &Report, tolerance: f64) -> Vec<Change> {
+    let mut changes = vec![];
+    for timing in &current.timings {
+        let Some(previous) = baseline.timing(timing.day) else { continue };
+        for ((phase, current_ms), (_, baseline_ms)) in timing.phases().into_iter().zip(previous.phases()) {
+            let (Some(current_ms), Some(baseline_ms)) = (current_ms, baseline_ms) else { continue };
+            let difference = current_ms - baseline_ms;
+            let trend = if difference.abs() < MIN_SIGNIFICANT_MS || difference.abs() <= baseline_ms * tolerance {
+                Trend::Unchanged
+            } else if difference < 0.0 {
+                Trend::Faster
+            } else {
+                Trend::Slower
+            };
+            changes.push(Change { day: timing.day, phase, baseline_ms, current_ms, trend });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::benchmark::*;
+
+    fn report() -> Report {
+        Report {
+            timings: vec![
+                Timing { day: 5, parse_ms: Some(0.25), part1_ms: 1.5, part2_ms: 120000.0 },
+                Timing { day: 12, parse_ms: Some(2.0), part1_ms: 16000.0, part2_ms: 100.0 },
+                Timing { day: 25, parse_ms: None, part1_ms: 300.0, part2_ms: 0.0 },
+            ]
+        }
+    }
+
+    #[test]
+    fn it_measures_every_phase() {
+        let input = indoc! {"
+        0 3 6 9 12 15
+        1 3 6 10 15 21
+        10 13 16 21 30 45"};
+
+        let timing = measure(9, input, 3).unwrap();
+        assert_eq!(9, timing.day);
+        assert!(timing.phases().iter().all(|(_, ms)| ms.is_some_and(|ms| ms >= 0.0)));
+        assert!(measure(9, "0 3 x", 1).is_err());
+
+        assert_eq!(None, measure(1, "1abc2", 1).unwrap().parse_ms);
+        assert_eq!(Err(SolveError::unsolvable(26, "days go from 1 to 25")), measure(26, input, 1));
+        assert_eq!(Err(SolveError::unsolvable(0, "days go from 1 to 25")), measure(0, input, 1));
+    }
+
+    #[test]
+    fn it_writes_and_reads_reports() {
+        assert_eq!(Ok(report()), Report::from_json(&report().to_json()));
+        assert_eq!(Ok(report()), Report::from_csv(&report().to_csv()));
+        assert_eq!(
+            "day,parse_ms,part1_ms,part2_ms\n5,0.250,1.500,120000.000\n12,2.000,16000.000,100.000\n25,,300.000,0.000\n",
+            report().to_csv()
+        );
+        assert!(report().to_json().contains(r#""parse_ms": null"#));
+        assert!(report().to_string().contains(" 25            -      300.000        0.000"));
+        assert!(Report::from_csv("day,parse_ms,part1_ms,part2_ms\n5,0.250").is_err());
+    }
+
+    #[test]
+    fn it_compares_with_a_baseline() {
+        let current = Report {
+            timings: vec![
+                Timing { day: 5, parse_ms: Some(0.5), part1_ms: 1.6, part2_ms: 50.0 },
+                Timing { day: 12, parse_ms: Some(2.1), part1_ms: 20000.0, part2_ms: 95.0 },
+                Timing { day: 13, parse_ms: Some(1.0), part1_ms: 1.0, part2_ms: 1.0 },
+                Timing { day: 25, parse_ms: None, part1_ms: 300.0, part2_ms: 0.0 },
+            ]
+        };
+
+        let trends = compare(&report(), &current, 0.1).iter()
+            .map(|c| (c.day, c.phase, c.trend))
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec![
+            (5, "parse", Trend::Unchanged),
+            (5, "part1", Trend::Unchanged),
+            (5, "part2", Trend::Faster),
+            (12, "parse", Trend::Unchanged),
+            (12, "part1", Trend::Slower),
+            (12, "part2", Trend::Unchanged),
+            (25, "part1", Trend::Unchanged),
+            (25, "part2", Trend::Unchanged),
+        ], trends);
+    }
+}
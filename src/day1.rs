@@ -1,18 +1,19 @@
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
-const NUMBERS_AS_WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+pub const NUMBERS_AS_WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
 
-fn sum_calibration_values(input: &str, extra_matches: &Vec<&str>) -> usize {
-    read_lines(input).iter()
-        .map(|line| line_number(line, extra_matches))
-        .sum()
+pub fn sum_calibration_values(input: &str, extra_matches: &Vec<&str>) -> Result<usize, ParseError> {
+    let numbers = parse_lines(1, input, |line| line_number(line, extra_matches))?;
+    Ok(numbers.iter().sum())
 }
 
-fn line_number(line: &str, extra_matches: &Vec<&str>) -> usize {
-    let numbers = find_numbers(line, extra_matches);
-    format!("{}{}", numbers.first().unwrap(), numbers.last().unwrap())
-        .parse::<usize>()
-        .unwrap()
+fn line_number(line: &Line, extra_matches: &Vec<&str>) -> Result<usize, ParseError> {
+    let numbers = find_numbers(line.text, extra_matches);
+    match (numbers.first(), numbers.last()) {
+        (Some(first), Some(last)) => Ok(first * 10 + last),
+        _ => Err(line.error(line.text, "expected at least one digit"))
+    }
 }
 
 fn find_numbers(line: &str, extra_matches: &Vec<&str>) -> Vec<usize> {
@@ -36,6 +37,18 @@ fn convert_number(number: &str) -> usize {
     }
 }
 
+pub struct Day1;
+
+impl Solution for Day1 {
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(sum_calibration_values(input, &vec![])?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(sum_calibration_values(input, &NUMBERS_AS_WORDS.to_vec())?.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -51,14 +64,14 @@ mod tests {
         a1b2c3d4e5f
         treb7uchet"};
 
-        assert_eq!(142, sum_calibration_values(input, &vec![]));
+        assert_eq!(Ok(142), sum_calibration_values(input, &vec![]));
     }
 
     #[test]
     fn it_solves_puzzle_part_1() {
         let input = read_input_file("input_day01.txt");
 
-        assert_eq!(55090, sum_calibration_values(&input, &vec![]));
+        assert_eq!(Ok(55090), sum_calibration_values(&input, &vec![]));
     }
 
     #[test]
@@ -72,13 +85,22 @@ mod tests {
         zoneight234
         7pqrstsixteen"};
 
-        assert_eq!(281, sum_calibration_values(input, &NUMBERS_AS_WORDS.to_vec()));
+        assert_eq!(Ok(281), sum_calibration_values(input, &NUMBERS_AS_WORDS.to_vec()));
     }
 
     #[test]
     fn it_solves_puzzle_part_2() {
         let input = read_input_file("input_day01.txt");
 
-        assert_eq!(54845, sum_calibration_values(&input, &NUMBERS_AS_WORDS.to_vec()));
+        assert_eq!(Ok(54845), sum_calibration_values(&input, &NUMBERS_AS_WORDS.to_vec()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_reports_a_line_without_digits() {
+        let input = indoc! {"
+        1abc2
+        pqrstu"};
+
+        assert_eq!(Err(ParseError::new(1, 2, 1, "expected at least one digit")), sum_calibration_values(input, &vec![]));
+    }
+}
@@ -1,218 +1,105 @@
 use std::ops::Div;
 use itertools::Itertools;
 
-use crate::day10::Direction::*;
-use crate::input_reader::read_lines;
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
-struct Position(usize, usize);
+use crate::grid::{Direction, Grid, Point};
+use crate::grid::Direction::*;
+use crate::parse_error::ParseError;
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
 struct Animal {
-    position: Position,
+    position: Point,
     direction: Direction,
 }
 
-fn walk(animal: &Animal, maze: &Vec<Vec<char>>) -> Option<Animal> {
-    let c = maze[animal.position.1][animal.position.0];
-    match c {
-        '|' => walk_from_vertical_pipe(animal),
-        '-' => walk_from_horizontal_pipe(animal),
-        'L' => walk_from_l(animal),
-        'J' => walk_from_j(animal),
-        '7' => walk_from_7(animal),
-        'F' => walk_from_f(animal),
-        '.' => walk_from_dot(animal),
-        _ => panic!("unknown tile")
-    }
-}
-
-fn walk_from_start(animal: &Animal) -> Option<Animal> {
-    let animal = match animal.direction {
-        North => Animal {
-            position: Position(animal.position.0, animal.position.1 - 1),
-            direction: North,
-        },
-        South => Animal {
-            position: Position(animal.position.0, animal.position.1 + 1),
-            direction: South,
-        },
-        East => Animal {
-            position: Position(animal.position.0 + 1, animal.position.1),
-            direction: East,
-        },
-        West => Animal {
-            position: Position(animal.position.0 - 1, animal.position.1),
-            direction: West,
-        }
-    };
-
-    Some(animal)
+fn walk(animal: &Animal, maze: &Grid<char>) -> Option<Animal> {
+    let direction = turn(*maze.get(animal.position)?, animal.direction)?;
+    Some(Animal { position: animal.position.step(direction), direction })
 }
 
-fn walk_from_dot(_animal: &Animal) -> Option<Animal> {
-    None
-}
-
-fn walk_from_l(animal: &Animal) -> Option<Animal> {
-    match animal.direction {
-        South => Some(Animal {
-            position: Position(animal.position.0 + 1, animal.position.1),
-            direction: East,
-        }),
-        West => Some(Animal {
-            position: Position(animal.position.0, animal.position.1 - 1),
-            direction: North,
-        }),
+// the direction leaving a pipe entered while heading to `direction`
+fn turn(pipe: char, direction: Direction) -> Option<Direction> {
+    match (pipe, direction) {
+        ('|', Up | Down) | ('-', Left | Right) => Some(direction),
+        ('L', Down) | ('F', Up) => Some(Right),
+        ('L', Left) | ('J', Right) => Some(Up),
+        ('J', Down) | ('7', Up) => Some(Left),
+        ('7', Right) | ('F', Left) => Some(Down),
         _ => None
     }
 }
 
-fn walk_from_j(animal: &Animal) -> Option<Animal> {
-    match animal.direction {
-        South => Some(Animal {
-            position: Position(animal.position.0 - 1, animal.position.1),
-            direction: West,
-        }),
-        East => Some(Animal {
-            position: Position(animal.position.0, animal.position.1 - 1),
-            direction: North,
-        }),
-        _ => None
-    }
+pub fn calculate_steps(input: &str) -> Result<usize, SolveError> {
+    Ok(find_polygon(input)?.len().div(2))
 }
 
-fn walk_from_7(animal: &Animal) -> Option<Animal> {
-    match animal.direction {
-        North => Some(Animal {
-            position: Position(animal.position.0 - 1, animal.position.1),
-            direction: West,
-        }),
-        East => Some(Animal {
-            position: Position(animal.position.0, animal.position.1 + 1),
-            direction: South,
-        }),
-        _ => None
-    }
-}
-
-fn walk_from_f(animal: &Animal) -> Option<Animal> {
-    match animal.direction {
-        North => Some(Animal {
-            position: Position(animal.position.0 + 1, animal.position.1),
-            direction: East,
-        }),
-        West => Some(Animal {
-            position: Position(animal.position.0, animal.position.1 + 1),
-            direction: South,
-        }),
-        _ => None
-    }
-}
-
-fn walk_from_vertical_pipe(animal: &Animal) -> Option<Animal> {
-    match animal.direction {
-        North => Some(Animal {
-            position: Position(animal.position.0, animal.position.1 - 1),
-            direction: North,
-        }),
-        South => Some(Animal {
-            position: Position(animal.position.0, animal.position.1 + 1),
-            direction: South,
-        }),
-        _ => None
-    }
-}
+// the pipes must form a closed loop through the starting tile
+pub fn find_polygon(input: &str) -> Result<Vec<Point>, SolveError> {
+    let maze = parse_maze(input)?;
 
-fn walk_from_horizontal_pipe(animal: &Animal) -> Option<Animal> {
-    match animal.direction {
-        West => Some(Animal {
-            position: Position(animal.position.0 - 1, animal.position.1),
-            direction: West,
-        }),
-        East => Some(Animal {
-            position: Position(animal.position.0 + 1, animal.position.1),
-            direction: East,
-        }),
-        _ => None
-    }
-}
-
-fn calculate_steps(input: &str) -> usize {
-    find_polygon(input).iter().count().div(2)
-}
-
-fn find_polygon(input: &str) -> Vec<Position> {
-    let maze: Vec<_> = parse_maze(input);
-
-    let starting_position = find_starting_position(&maze);
+    let starting_position = maze.position(|&c| c == 'S')
+        .ok_or_else(|| ParseError::end_of_input(10, maze.height() + 1, "a starting tile `S`"))?;
 
     let mut polygon = vec![starting_position];
 
-    let mut current = start_walking(&maze, starting_position);
+    let open_loop = || SolveError::unsolvable(10, "the pipes from the starting tile do not form a loop");
+    let mut current = start_walking(&maze, starting_position).ok_or_else(open_loop)?;
     polygon.push(current.position);
 
     while current.position != starting_position {
-        current = walk(&current, &maze).unwrap();
+        current = walk(&current, &maze).ok_or_else(open_loop)?;
         polygon.push(current.position);
     }
 
-    polygon
+    Ok(polygon)
 }
 
-fn start_walking(maze: &Vec<Vec<char>>, starting_position: Position) -> Animal {
-    [North, South, East, West]
+fn start_walking(maze: &Grid<char>, starting_position: Point) -> Option<Animal> {
+    Direction::ALL
         .iter()
-        .map(|&direction| Animal { position: starting_position, direction })
-        .filter_map(|animal| walk_from_start(&animal))
-        .find_or_first(|animal| walk(&animal, &maze) != None)
-        .unwrap()
-}
-
-fn find_starting_position(maze: &Vec<Vec<char>>) -> Position {
-    maze.iter().enumerate()
-        .find_map(|(y, line)| {
-            line.iter().position(|&c| c == 'S').map(|x| Position(x, y))
-        }).unwrap()
+        .map(|&direction| Animal { position: starting_position.step(direction), direction })
+        .filter(|animal| maze.contains(animal.position))
+        .find_or_first(|animal| walk(animal, maze).is_some())
 }
 
-fn parse_maze(input: &str) -> Vec<Vec<char>> {
-    read_lines(input).iter()
-        .map(|line| line.chars().collect::<Vec<_>>())
-        .collect()
+pub fn parse_maze(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(10, input, "|-LJ7F.S")
 }
 
 // Calculate enclosed point using Pick's theorem
 // https://en.wikipedia.org/wiki/Pick's_theorem
-fn count_enclosed_points(input: &str) -> isize {
-    let polygon = find_polygon(input);
+pub fn count_enclosed_points(input: &str) -> Result<isize, SolveError> {
+    let polygon = find_polygon(input)?;
     let area = calculate_area(&polygon);
-    let b = polygon.iter().count() as isize;
-    area + 1 - (b / 2)
+    let b = polygon.len() as isize;
+    Ok(area + 1 - (b / 2))
 }
 
 // Calculate area using the Shoelace formula
 // https://en.wikipedia.org/wiki/Shoelace_formula
-fn calculate_area(polygon: &Vec<Position>) -> isize {
+fn calculate_area(polygon: &[Point]) -> isize {
     polygon.windows(2)
-        .map(|positions| {
-            let Position(x1, y1) = positions[0];
-            let Position(x2, y2) = positions[1];
-            (x1 * y2) as isize - (x2 * y1) as isize
-        })
+        .map(|points| points[0].x * points[1].y - points[1].x * points[0].y)
         .sum::<isize>()
         .abs()
         .div(2)
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_maze(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_steps(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(count_enclosed_points(input)?.into())
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -224,14 +111,14 @@ mod tests {
     #[test]
     fn it_solves_first_part() {
         let input = read_input_file("input_day10.txt");
-        assert_eq!(6812, calculate_steps(&input));
+        assert_eq!(Ok(6812), calculate_steps(&input));
     }
 
     #[test]
     fn it_solves_second_part() {
         let input = read_input_file("input_day10.txt");
 
-        assert_eq!(527, count_enclosed_points(&input));
+        assert_eq!(Ok(527), count_enclosed_points(&input));
     }
 
     #[test]
@@ -247,7 +134,7 @@ mod tests {
         .L--J.L--J.
         ..........."};
 
-        assert_eq!(4, count_enclosed_points(input));
+        assert_eq!(Ok(4), count_enclosed_points(input));
     }
 
     #[test]
@@ -258,7 +145,7 @@ mod tests {
         .|.|.
         .L-J.
         ....."};
-        assert_eq!(4, calculate_steps(input));
+        assert_eq!(Ok(4), calculate_steps(input));
 
         let input = indoc! {"
         ...F7.
@@ -266,6 +153,26 @@ mod tests {
         .SJ.L7
         .|F--J
         .LJ..."};
-        assert_eq!(8, calculate_steps(input));
+        assert_eq!(Ok(8), calculate_steps(input));
+    }
+
+    #[test]
+    fn it_reports_an_unknown_tile() {
+        let input = indoc! {"
+        .....
+        .S-7.
+        .|x|.
+        .L-J."};
+
+        assert_eq!(Err(SolveError::Parse(ParseError::new(10, 3, 3, "unknown cell `x`"))), calculate_steps(input));
+        assert_eq!(Err(SolveError::Parse(ParseError::end_of_input(10, 3, "a starting tile `S`"))), calculate_steps(".F7.\n.LJ."));
+    }
+
+    #[test]
+    fn it_reports_an_open_loop() {
+        let open_loop = Err(SolveError::unsolvable(10, "the pipes from the starting tile do not form a loop"));
+        assert_eq!(open_loop, calculate_steps(".S-.."));
+        assert_eq!(open_loop, calculate_steps("S"));
+        assert_eq!(open_loop.map(|_: usize| 0), count_enclosed_points(".S-.."));
     }
-}
\ No newline at end of file
+}
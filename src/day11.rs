@@ -1,12 +1,14 @@
 use itertools::Itertools;
 
-use crate::input_reader::read_lines;
+use crate::grid::Grid;
+use crate::parse_error::ParseError;
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Debug, Clone, PartialEq)]
-struct Galaxy {
-    index: usize,
-    x: usize,
-    y: usize,
+pub struct Galaxy {
+    pub index: usize,
+    pub x: usize,
+    pub y: usize,
 }
 
 impl Galaxy {
@@ -18,12 +20,12 @@ impl Galaxy {
         self.x += expansion;
     }
 
-    fn distance(&self, other: &Galaxy) -> usize {
+    pub fn distance(&self, other: &Galaxy) -> usize {
         ((self.y as isize - other.y as isize).abs() + (self.x as isize - other.x as isize).abs()) as usize
     }
 }
 
-fn calculate_sum_distances(galaxies: Vec<Galaxy>) -> usize {
+pub fn calculate_sum_distances(galaxies: Vec<Galaxy>) -> usize {
     galaxies.into_iter().combinations(2)
         .map(|combination| {
             let first = combination.first().unwrap();
@@ -32,8 +34,8 @@ fn calculate_sum_distances(galaxies: Vec<Galaxy>) -> usize {
         }).sum()
 }
 
-fn expand_galaxies(input: &str, expansion_factor: usize) -> Vec<Galaxy> {
-    let mut galaxies = parse_galaxies(input);
+pub fn expand_galaxies(input: &str, expansion_factor: usize) -> Result<Vec<Galaxy>, ParseError> {
+    let mut galaxies = parse_galaxies(input)?;
 
     expand_galaxies_along_one_dimension(
         &mut galaxies,
@@ -49,17 +51,17 @@ fn expand_galaxies(input: &str, expansion_factor: usize) -> Vec<Galaxy> {
         |g: &mut Galaxy, expansion: usize| g.expand_column(expansion),
     );
 
-    galaxies
+    Ok(galaxies)
 }
 
-fn expand_galaxies_along_one_dimension(galaxies: &mut Vec<Galaxy>,
+fn expand_galaxies_along_one_dimension(galaxies: &mut [Galaxy],
                                        expansion_factor: usize,
                                        dimension: fn(&Galaxy) -> usize,
                                        galaxy_expander: fn(&mut Galaxy, usize)) {
-    galaxies.sort_by(|g1: &Galaxy, g2: &Galaxy| dimension(g1).cmp(&dimension(g2)));
+    galaxies.sort_by_key(&dimension);
 
-    let number_of_galaxies = galaxies.iter().count();
-    for galaxy_index in 0..number_of_galaxies - 1 {
+    let number_of_galaxies = galaxies.len();
+    for galaxy_index in 0..number_of_galaxies.saturating_sub(1) {
         let g1 = galaxies.get(galaxy_index).unwrap();
         let g2 = galaxies.get(galaxy_index + 1).unwrap();
 
@@ -76,19 +78,30 @@ fn expand_galaxies_along_one_dimension(galaxies: &mut Vec<Galaxy>,
     }
 }
 
-fn parse_galaxies(input: &str) -> Vec<Galaxy> {
-    let rows = read_lines(input);
-    let mut galaxy_index: usize = 1;
-    let mut galaxies: Vec<Galaxy> = vec![];
-    for (y, row) in rows.iter().enumerate() {
-        for (x, c) in row.chars().enumerate() {
-            if c == '#' {
-                galaxies.push(Galaxy { index: galaxy_index, x, y });
-                galaxy_index += 1;
-            }
-        }
+pub fn parse_galaxies(input: &str) -> Result<Vec<Galaxy>, ParseError> {
+    let image = Grid::parse(11, input, "#.")?;
+    let galaxies = image.iter()
+        .filter(|(_, &c)| c == '#')
+        .enumerate()
+        .map(|(index, (point, _))| Galaxy { index: index + 1, x: point.x as usize, y: point.y as usize })
+        .collect();
+    Ok(galaxies)
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_galaxies(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_sum_distances(expand_galaxies(input, 2)?).into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_sum_distances(expand_galaxies(input, 1000000)?).into())
     }
-    galaxies
 }
 
 #[cfg(test)]
@@ -102,8 +115,8 @@ mod tests {
     fn it_solves_puzzle() {
         let input = read_input_file("input_day11.txt");
 
-        assert_eq!(9648398, calculate_sum_distances(expand_galaxies(&input, 2)));
-        assert_eq!(618800410814, calculate_sum_distances(expand_galaxies(&input, 1000000)));
+        assert_eq!(9648398, calculate_sum_distances(expand_galaxies(&input, 2).unwrap()));
+        assert_eq!(618800410814, calculate_sum_distances(expand_galaxies(&input, 1000000).unwrap()));
     }
 
     #[test]
@@ -120,8 +133,8 @@ mod tests {
         .......#..
         #...#....."};
 
-        assert_eq!(374, calculate_sum_distances(expand_galaxies(input, 2)));
-        assert_eq!(1030, calculate_sum_distances(expand_galaxies(input, 10)));
-        assert_eq!(8410, calculate_sum_distances(expand_galaxies(input, 100)));
+        assert_eq!(374, calculate_sum_distances(expand_galaxies(input, 2).unwrap()));
+        assert_eq!(1030, calculate_sum_distances(expand_galaxies(input, 10).unwrap()));
+        assert_eq!(8410, calculate_sum_distances(expand_galaxies(input, 100).unwrap()));
     }
 }
\ No newline at end of file
@@ -1,106 +1,285 @@
-use std::collections::LinkedList;
-
-use memoize::memoize;
-
-use crate::input_reader::read_lines;
-
-fn numbers_of_valid_springs(input: &str, expand: bool) -> Vec<usize> {
-    read_lines(input).iter()
-        .map(|s| {
-            let parts: Vec<_> = s.split(" ").collect();
-            let mut spring = parts[0].to_string();
-            let mut size_continuous_damaged_groups: Vec<_> = parts[1].split(",").map(|s| s.parse::<usize>().unwrap()).collect();
-            if expand {
-                spring = std::iter::repeat(spring).take(5).collect::<Vec<_>>().join("?");
-                size_continuous_damaged_groups = size_continuous_damaged_groups.iter().cycle()
-                    .take(size_continuous_damaged_groups.len() * 5)
-                    .cloned().
-                    collect();
-            }
-            let spring1 = spring.chars().collect::<LinkedList<_>>();
-            let groups_size = size_continuous_damaged_groups.iter().map(|i| *i).collect::<LinkedList<usize>>();
-            calculate_number_of_valid_springs(spring1, groups_size, 0)
-        }).collect()
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::parse_error::{Line, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
+
+// a record is unfolded by repeating its springs `repeats` times, joined by the `joiner` spring,
+// and its groups `repeats` times
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Unfolding {
+    repeats: usize,
+    joiner: char,
 }
 
-#[memoize]
-fn calculate_number_of_valid_springs(spring: LinkedList<char>, groups_size: LinkedList<usize>, damages_springs_counter: usize) -> usize {
-    if spring.is_empty() && damages_springs_counter != 0 { // we saw damaged springs
-        return if groups_size.len() == 1 && damages_springs_counter == *groups_size.front().unwrap() {
-            1
-        } else {
-            0
-        };
+impl Unfolding {
+    pub const FOLDED: Unfolding = Unfolding { repeats: 1, joiner: '?' };
+    pub const UNFOLDED: Unfolding = Unfolding { repeats: 5, ..Unfolding::FOLDED };
+
+    // none when the joiner is not a spring
+    pub fn new(repeats: usize, joiner: char) -> Option<Self> {
+        ".#?".contains(joiner).then_some(Unfolding { repeats, joiner })
     }
 
-    if spring.is_empty() && damages_springs_counter == 0 { // we never saw damaged springs
-        return if groups_size.is_empty() { // it wasn't supposed to have damaged springs
-            1
-        } else {
-            0
-        };
+    pub fn repeats(&self) -> usize {
+        self.repeats
     }
 
-    if damages_springs_counter > 0 && groups_size.is_empty() { // we saw more damaged spring than expected
-        return 0;
+    pub fn joiner(&self) -> char {
+        self.joiner
     }
 
-    if damages_springs_counter > 0 && damages_springs_counter > *groups_size.front().unwrap() { // we saw more damaged spring than expected
-        return 0;
+    pub fn unfold(&self, spring: &str, groups: &[usize]) -> (String, Vec<usize>) {
+        let spring = std::iter::repeat_n(spring, self.repeats).collect::<Vec<_>>().join(&self.joiner.to_string());
+        let groups = groups.iter().cycle().take(groups.len() * self.repeats).cloned().collect();
+        (spring, groups)
     }
+}
+
+pub fn numbers_of_valid_springs(input: &str, unfolding: Unfolding) -> Result<Vec<usize>, ParseError> {
+    let records = parse_lines(12, input, parse_record)?;
+    let numbers = records.into_par_iter()
+        .map(|(spring, size_continuous_damaged_groups)| {
+            let (spring, groups) = unfolding.unfold(&spring, &size_continuous_damaged_groups);
+            count_arrangements(spring.as_bytes(), &groups)
+        }).collect();
+    Ok(numbers)
+}
 
-    let mut spring = spring;
-    let next = spring.pop_front().unwrap();
+// for every record, the number of arrangements when it is repeated 1, 2, ... `max_repeats` times
+pub fn arrangements_growth(input: &str, joiner: char, max_repeats: usize) -> Result<Vec<Vec<usize>>, SolveError> {
+    let unfolding = Unfolding::new(1, joiner)
+        .ok_or_else(|| SolveError::unsolvable(12, format!("the joiner must be a spring, found `{}`", joiner)))?;
+    let records = parse_lines(12, input, parse_record)?;
+    let growth = records.into_par_iter()
+        .map(|(spring, groups)| {
+            (1..=max_repeats)
+                .map(|repeats| {
+                    let (spring, groups) = Unfolding { repeats, ..unfolding }.unfold(&spring, &groups);
+                    count_arrangements(spring.as_bytes(), &groups)
+                })
+                .collect()
+        }).collect();
+    Ok(growth)
+}
 
-    match next {
-        '#' => calculate_number_of_valid_springs(spring, groups_size, damages_springs_counter + 1),
+pub fn parse_record(line: &Line) -> Result<(String, Vec<usize>), ParseError> {
+    let (spring, groups) = line.split_once(line.text, " ")?;
+    if let Some(index) = spring.find(|c| !".#?".contains(c)) {
+        return Err(line.error(&spring[index..], format!("unknown spring `{}`", &spring[index..index + 1])));
+    }
+    let groups = groups.split(',')
+        .map(|g| line.parse::<usize>(g))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((spring.to_string(), groups))
+}
 
-        '.' => { // arrived at the end of a chunk
-            let mut groups_size = groups_size;
-            if damages_springs_counter > 0 { // we saw damaged springs
-                let expected_size = groups_size.pop_front().unwrap();
-                if damages_springs_counter != expected_size {
-                    return 0;
-                }
+pub fn count_arrangements(spring: &[u8], groups: &[usize]) -> usize {
+    Row::from_bytes(spring, groups).count()
+}
+
+// a record whose arrangements are counted once, then enumerated, sampled or analysed
+pub struct Row {
+    spring: Vec<u8>,
+    groups: Vec<usize>,
+    // how many springs from i on could be damaged
+    run: Vec<usize>,
+    // table[i][g] counts the arrangements of spring[i..] with the groups[g..],
+    // index `spring.len() + 1` stands for the end of the row reached after a group
+    table: Vec<Vec<usize>>,
+}
+
+impl Row {
+    pub fn new(spring: &str, groups: &[usize]) -> Self {
+        Row::from_bytes(spring.as_bytes(), groups)
+    }
+
+    fn from_bytes(spring: &[u8], groups: &[usize]) -> Self {
+        let length = spring.len();
+        let mut run = vec![0; length + 1];
+        for i in (0..length).rev() {
+            run[i] = if spring[i] == b'.' { 0 } else { run[i + 1] + 1 };
+        }
+
+        let mut row = Row { spring: spring.to_vec(), groups: groups.to_vec(), run, table: vec![vec![0; groups.len() + 1]; length + 2] };
+        row.table[length][groups.len()] = 1;
+        row.table[length + 1][groups.len()] = 1;
+
+        for i in (0..length).rev() {
+            for g in 0..=groups.len() {
+                row.table[i][g] = row.operational_ways(i, g) + row.damaged_ways(i, g);
             }
-            calculate_number_of_valid_springs(spring, groups_size, 0)
         }
 
-        _ => { // if it's a ?
-            if groups_size.is_empty() { // we can continue checking if we find damaged springs or not
-                return calculate_number_of_valid_springs(spring, groups_size, 0);
+        row
+    }
+
+    // the arrangements where the spring i is operational
+    fn operational_ways(&self, i: usize, g: usize) -> usize {
+        if self.spring[i] != b'#' { self.table[i + 1][g] } else { 0 }
+    }
+
+    // the arrangements where the group g starts at i and is followed by an operational spring
+    fn damaged_ways(&self, i: usize, g: usize) -> usize {
+        if self.fits(i, g) { self.table[i + self.groups[g] + 1][g + 1] } else { 0 }
+    }
+
+    fn fits(&self, i: usize, g: usize) -> bool {
+        let Some(&size) = self.groups.get(g) else { return false };
+        self.run[i] >= size && (i + size == self.spring.len() || self.spring[i + size] != b'#')
+    }
+
+    pub fn count(&self) -> usize {
+        self.table[0][0]
+    }
+
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let stack = if self.count() > 0 { vec![(0, 0, vec![])] } else { vec![] };
+        Arrangements { row: self, stack }
+    }
+
+    // every arrangement has the same chance to be picked
+    pub fn sample(&self, random: &mut impl Rng) -> Option<String> {
+        if self.count() == 0 {
+            return None;
+        }
+
+        let length = self.spring.len();
+        let (mut i, mut g) = (0, 0);
+        let mut arrangement = Vec::with_capacity(length);
+        while i < length {
+            let operational = self.operational_ways(i, g);
+            if random.gen_range(0..self.table[i][g]) < operational {
+                arrangement.push(b'.');
+                i += 1;
+            } else {
+                arrangement.extend(self.group_at(i, g));
+                i += self.groups[g] + 1;
+                g += 1;
             }
+        }
+
+        arrangement.truncate(length);
+        Some(String::from_utf8(arrangement).unwrap())
+    }
+
+    fn group_at(&self, i: usize, g: usize) -> Vec<u8> {
+        let mut group = vec![b'#'; self.groups[g]];
+        if i + self.groups[g] < self.spring.len() {
+            group.push(b'.');
+        }
+        group
+    }
 
-            if *groups_size.front().unwrap() == damages_springs_counter { // we found a valid group of damaged springs let's move on. let's suppose that ? is a  '.'.
-                let mut groups_size = groups_size;
-                groups_size.pop_front();
-                return calculate_number_of_valid_springs(spring, groups_size, 0);
+    // the row with the springs that are the same in every arrangement, the others are left as `?`
+    pub fn forced_cells(&self) -> Option<String> {
+        let total = self.count();
+        if total == 0 {
+            return None;
+        }
+
+        let length = self.spring.len();
+        // before[i][g] counts the arrangements of spring[..i] with the groups[..g] where a group can start at i
+        let mut before = vec![vec![0; self.groups.len() + 1]; length + 2];
+        before[0][0] = 1;
+        // damaged[i] counts the arrangements where the spring i is damaged, as differences
+        let mut damaged = vec![0isize; length + 1];
+        for i in 0..length {
+            for g in 0..=self.groups.len() {
+                let ways = before[i][g];
+                if ways == 0 {
+                    continue;
+                }
+                if self.spring[i] != b'#' {
+                    before[i + 1][g] += ways;
+                }
+                if self.fits(i, g) {
+                    let end = i + self.groups[g];
+                    before[end + 1][g + 1] += ways;
+                    let through = (ways * self.table[end + 1][g + 1]) as isize;
+                    damaged[i] += through;
+                    damaged[end] -= through;
+                }
             }
+        }
 
-            if damages_springs_counter > 0 { // we are still counting a spring group. let's suppose that ? is a '#'.
-                return calculate_number_of_valid_springs(spring, groups_size, damages_springs_counter + 1);
+        let mut count = 0;
+        let cells = (0..length).map(|i| {
+            count += damaged[i];
+            match count as usize {
+                c if c == total => '#',
+                0 => '.',
+                _ => '?'
             }
+        });
+        Some(cells.collect())
+    }
+}
+
+pub struct Arrangements<'a> {
+    row: &'a Row,
+    stack: Vec<(usize, usize, Vec<u8>)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
 
-            calculate_number_of_valid_springs(spring.clone(), groups_size.clone(), 1)  // let's consider that ? is a '#'.
-                + calculate_number_of_valid_springs(spring.clone(), groups_size.clone(), 0) // let's consider that ? is a '.'.
+    // only the branches leading to an arrangement are explored
+    fn next(&mut self) -> Option<String> {
+        let row = self.row;
+        while let Some((i, g, mut arrangement)) = self.stack.pop() {
+            if i >= row.spring.len() {
+                arrangement.truncate(row.spring.len());
+                return Some(String::from_utf8(arrangement).unwrap());
+            }
+
+            if row.damaged_ways(i, g) > 0 {
+                let mut damaged = arrangement.clone();
+                damaged.extend(row.group_at(i, g));
+                self.stack.push((i + row.groups[g] + 1, g + 1, damaged));
+            }
+            if row.operational_ways(i, g) > 0 {
+                arrangement.push(b'.');
+                self.stack.push((i + 1, g, arrangement));
+            }
         }
+        None
+    }
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_lines(12, input, parse_record).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(numbers_of_valid_springs(input, Unfolding::FOLDED)?.iter().sum::<usize>().into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(numbers_of_valid_springs(input, Unfolding::UNFOLDED)?.iter().sum::<usize>().into())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{HashMap, HashSet};
+
     use indoc::indoc;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
     use crate::day12::*;
     use crate::input_reader::*;
 
     #[test]
-    #[ignore] // completes in more than 16 seconds
     fn it_solves_first_part() {
         let input = read_input_file("input_day12.txt");
 
-        assert_eq!(7251, numbers_of_valid_springs(&input, false).iter().sum::<usize>());
-        assert_eq!(2128386729962, numbers_of_valid_springs(&input, true).iter().sum::<usize>());
+        assert_eq!(7251, numbers_of_valid_springs(&input, Unfolding::FOLDED).unwrap().iter().sum::<usize>());
+        assert_eq!(2128386729962, numbers_of_valid_springs(&input, Unfolding::UNFOLDED).unwrap().iter().sum::<usize>());
     }
 
     #[test]
@@ -113,9 +292,76 @@ mod tests {
         ????.######..#####. 1,6,5
         ?###???????? 3,2,1"};
 
-        assert_eq!(vec![1, 4, 1, 1, 4, 10], numbers_of_valid_springs(input, false));
-        assert_eq!(vec![1, 16384, 1, 16, 2500, 506250], numbers_of_valid_springs(input, true));
-        assert_eq!(21, numbers_of_valid_springs(input, false).iter().sum::<usize>());
-        assert_eq!(525152, numbers_of_valid_springs(input, true).iter().sum::<usize>());
+        assert_eq!(vec![1, 4, 1, 1, 4, 10], numbers_of_valid_springs(input, Unfolding::FOLDED).unwrap());
+        assert_eq!(vec![1, 16384, 1, 16, 2500, 506250], numbers_of_valid_springs(input, Unfolding::UNFOLDED).unwrap());
+        assert_eq!(21, numbers_of_valid_springs(input, Unfolding::FOLDED).unwrap().iter().sum::<usize>());
+        assert_eq!(525152, numbers_of_valid_springs(input, Unfolding::UNFOLDED).unwrap().iter().sum::<usize>());
+    }
+
+    #[test]
+    fn it_unfolds_records() {
+        let unfolding = Unfolding::new(3, '.').unwrap();
+        assert_eq!((".#..#..#".to_string(), vec![1, 1, 1]), unfolding.unfold(".#", &[1]));
+        assert_eq!(vec![16, 50], numbers_of_valid_springs(".??..??...?##. 1,1,3\n?###???????? 3,2,1", Unfolding::new(2, '#').unwrap()).unwrap());
+
+        let growth = arrangements_growth(".??..??...?##. 1,1,3\n????.#...#... 4,1,1", '?', 5).unwrap();
+        assert_eq!(vec![vec![4, 32, 256, 2048, 16384], vec![1, 2, 4, 8, 16]], growth);
+        // both records grow geometrically
+        assert!(growth.iter().all(|counts| counts.windows(2).all(|w| w[1] * counts[0] == w[0] * counts[1])));
+
+        assert_eq!(None, Unfolding::new(2, 'x'));
+        assert_eq!(Err(SolveError::unsolvable(12, "the joiner must be a spring, found `x`")), arrangements_growth("?# 1", 'x', 2));
+    }
+
+    #[test]
+    fn it_counts_arrangements() {
+        assert_eq!(1, count_arrangements(b"???.###", &[1, 1, 3]));
+        assert_eq!(10, count_arrangements(b"?###????????", &[3, 2, 1]));
+        assert_eq!(1, count_arrangements(b"...", &[]));
+        assert_eq!(0, count_arrangements(b"#", &[]));
+        assert_eq!(0, count_arrangements(b"##", &[1]));
+        assert_eq!(1, count_arrangements(b"", &[]));
+    }
+
+    #[test]
+    fn it_enumerates_arrangements() {
+        let row = Row::new("?###????????", &[3, 2, 1]);
+
+        let arrangements: Vec<_> = row.arrangements().collect();
+        assert_eq!(10, arrangements.len());
+        assert_eq!(".###....##.#", arrangements[0]);
+        assert!(arrangements.iter().all(|a| Row::new(a, &[3, 2, 1]).count() == 1));
+        assert_eq!(vec!["#.#.###"], Row::new("???.###", &[1, 1, 3]).arrangements().collect::<Vec<_>>());
+        assert_eq!(0, Row::new("#.#", &[3]).arrangements().count());
+    }
+
+    #[test]
+    fn it_samples_arrangements_uniformly() {
+        let row = Row::new(".??..??...?##.", &[1, 1, 3]);
+        let mut random = StdRng::seed_from_u64(12);
+
+        let mut samples: HashMap<String, usize> = HashMap::new();
+        for _ in 0..4000 {
+            *samples.entry(row.sample(&mut random).unwrap()).or_default() += 1;
+        }
+
+        assert_eq!(row.arrangements().collect::<HashSet<_>>(), samples.keys().cloned().collect());
+        assert!(samples.values().all(|&count| (900..1100).contains(&count)));
+        assert_eq!(None, Row::new("#.#", &[3]).sample(&mut random));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_finds_forced_cells() {
+        assert_eq!(Some(".###.???????".to_string()), Row::new("?###????????", &[3, 2, 1]).forced_cells());
+        assert_eq!(Some("#.#.###".to_string()), Row::new("???.###", &[1, 1, 3]).forced_cells());
+        assert_eq!(Some("???????".to_string()), Row::new("???????", &[3]).forced_cells());
+        assert_eq!(Some("??###??".to_string()), Row::new("???????", &[5]).forced_cells());
+        assert_eq!(None, Row::new("#.#", &[3]).forced_cells());
+    }
+
+    #[test]
+    fn it_reports_a_malformed_record() {
+        assert_eq!(Err(ParseError::new(12, 2, 4, "unknown spring `X`")), numbers_of_valid_springs("???.### 1,1,3\n.??X.??...?##. 1,1,3", Unfolding::FOLDED));
+        assert_eq!(Err(ParseError::new(12, 1, 11, "expected a number, found `a`")), numbers_of_valid_springs("???.### 1,a,3", Unfolding::FOLDED));
+    }
+}
@@ -1,44 +1,44 @@
 use std::iter::zip;
 
-use itertools::Itertools;
-use num::range;
+use crate::grid::Grid;
+use crate::parse_error::{Line, lines, parse_grid_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
-use crate::input_reader::read_lines;
-
-fn summarize_notes(notes: &Vec<Vec<String>>, maximum_smudge: usize) -> usize {
-    notes.iter().map(|note| summarize_note(note, maximum_smudge)).sum::<usize>()
+pub fn summarize_notes(notes: &[Grid<char>], maximum_smudge: usize) -> Result<usize, SolveError> {
+    notes.iter().map(|note| summarize_note(note, maximum_smudge)).sum()
 }
 
-fn summarize_note(note: &Vec<String>, maximum_smudge: usize) -> usize {
+pub fn summarize_note(note: &Grid<char>, maximum_smudge: usize) -> Result<usize, SolveError> {
     if let Some(reflection_row) = finds_reflection_row(note, maximum_smudge) {
-        return reflection_row * 100;
+        return Ok(reflection_row * 100);
     }
 
     if let Some(reflection_column) = finds_reflection_column(note, maximum_smudge) {
-        return reflection_column;
+        return Ok(reflection_column);
     }
 
-    panic!("Should have find something!")
+    Err(SolveError::unsolvable(13, "a note has no line of reflection"))
 }
 
-fn finds_reflection_column(note: &Vec<String>, maximum_smudge: usize) -> Option<usize> {
-    finds_reflection_row(&flip_note(note), maximum_smudge)
+fn finds_reflection_column(note: &Grid<char>, maximum_smudge: usize) -> Option<usize> {
+    finds_reflection_row(&note.transpose(), maximum_smudge)
 }
 
-fn finds_reflection_row(note: &Vec<String>, maximum_smudge: usize) -> Option<usize> {
-    finds_candidate_reflections(note).iter()
-        .flat_map(|&candidate| try_candidate(candidate, note, maximum_smudge))
+fn finds_reflection_row(note: &Grid<char>, maximum_smudge: usize) -> Option<usize> {
+    let note: Vec<&[char]> = note.rows().collect();
+    finds_candidate_reflections(&note).iter()
+        .flat_map(|&candidate| try_candidate(candidate, &note, maximum_smudge))
         .next()
 }
 
-fn try_candidate(candidate: usize, note: &Vec<String>, maximum_smudge: usize) -> Option<usize> {
+fn try_candidate(candidate: usize, note: &[&[char]], maximum_smudge: usize) -> Option<usize> {
     let note_len = note.len() as isize;
     let mut current: isize = candidate as isize;
     let mut next: isize = (candidate + 1) as isize;
 
     let mut total_smudge: usize = 0;
     while current >= 0 && next < note_len {
-        total_smudge += smudge(&note[current as usize], &note[next as usize]);
+        total_smudge += smudge(note[current as usize], note[next as usize]);
         next += 1;
         current -= 1;
     }
@@ -50,36 +50,25 @@ fn try_candidate(candidate: usize, note: &Vec<String>, maximum_smudge: usize) ->
     Some(candidate + 1)
 }
 
-fn finds_candidate_reflections(note: &Vec<String>) -> Vec<usize> {
-    (0..note.len() - 1)
-        .filter(|&prev| smudge(&note[prev], &note[prev + 1]) <= 1)
+fn finds_candidate_reflections(note: &[&[char]]) -> Vec<usize> {
+    // an empty note has no pair of rows to reflect between
+    (0..note.len().saturating_sub(1))
+        .filter(|&prev| smudge(note[prev], note[prev + 1]) <= 1)
         .collect()
 }
 
-fn flip_note(note: &Vec<String>) -> Vec<String> {
-    let mut flipped_note = vec![];
-
-    for y in range(0, note[0].len()) {
-        let mut column: Vec<char> = vec![];
-        for row in note {
-            column.push(row.chars().nth(y).unwrap());
-        }
-        flipped_note.push(column.iter().join(""));
-    }
-
-    flipped_note
-}
-
-fn parse_notes(input: &str) -> Vec<Vec<String>> {
-    read_lines(input).split(|l| l.is_empty())
-        .map(|s| s.to_vec())
-        .collect::<Vec<_>>()
+// the notes are separated by empty lines
+pub fn parse_notes(input: &str) -> Result<Vec<Grid<char>>, ParseError> {
+    let lines: Vec<Line> = lines(13, input).collect();
+    lines.split(|line| line.text.is_empty())
+        .map(|note| parse_grid_lines(note, "#.").map(Grid::new))
+        .collect()
 }
 
-fn smudge(s1: &str, s2: &str) -> usize {
+fn smudge(s1: &[char], s2: &[char]) -> usize {
     let mut diff: usize = 0;
 
-    for (c1, c2) in zip(s1.chars(), s2.chars()) {
+    for (c1, c2) in zip(s1, s2) {
         if c1 != c2 {
             diff += 1;
         }
@@ -88,6 +77,22 @@ fn smudge(s1: &str, s2: &str) -> usize {
     diff
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_notes(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(summarize_notes(&parse_notes(input)?, 0)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(summarize_notes(&parse_notes(input)?, 1)?.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -99,8 +104,8 @@ mod tests {
     fn it_solves_puzzle() {
         let input = &read_input_file("input_day13.txt");
 
-        assert_eq!(30802, summarize_notes(&parse_notes(input), 0));
-        assert_eq!(37876, summarize_notes(&parse_notes(input), 1));
+        assert_eq!(Ok(30802), summarize_notes(&parse_notes(input).unwrap(), 0));
+        assert_eq!(Ok(37876), summarize_notes(&parse_notes(input).unwrap(), 1));
     }
 
     #[test]
@@ -122,10 +127,30 @@ mod tests {
         ..##..###
         #....#..#"};
 
-        let notes = parse_notes(input);
-        assert_eq!(5, summarize_note(&notes[0], 0));
-        assert_eq!(400, summarize_note(&notes[1], 0));
-        assert_eq!(405, summarize_notes(&notes, 0));
-        assert_eq!(400, summarize_notes(&notes, 1));
+        let notes = parse_notes(input).unwrap();
+        assert_eq!(Ok(5), summarize_note(&notes[0], 0));
+        assert_eq!(Ok(400), summarize_note(&notes[1], 0));
+        assert_eq!(Ok(405), summarize_notes(&notes, 0));
+        assert_eq!(Ok(400), summarize_notes(&notes, 1));
+    }
+
+    #[test]
+    fn it_reports_a_note_without_reflection() {
+        let no_reflection = Err(SolveError::unsolvable(13, "a note has no line of reflection"));
+        assert_eq!(no_reflection, Day13.part1("#."));
+        assert_eq!(no_reflection, Day13.part1("..\n##\n\n\n.."));
+    }
+
+    #[test]
+    fn it_reports_a_ragged_note() {
+        let input = indoc! {"
+        #.##..##.
+        ..#.##.#.
+
+        #...##..#
+        #....#."};
+
+        assert_eq!(Err(ParseError::new(13, 5, 1, "expected a row of 9 cells, found 7")), parse_notes(input));
+        assert_eq!(Err(ParseError::new(13, 4, 3, "unknown cell `O`")), parse_notes("#.\n.#\n\n#.O"));
     }
-}
\ No newline at end of file
+}
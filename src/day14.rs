@@ -2,23 +2,23 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
-use crate::input_reader::read_lines;
+use crate::grid::{Direction, Grid, Point};
+use crate::grid::Direction::*;
+use crate::parse_error::ParseError;
+use crate::solution::{Answer, Solution, SolveError};
 
-#[derive(Debug, Hash, Clone, Eq, PartialEq)]
-struct Position(usize, usize);
+type Platform = (HashSet<Point>, HashSet<Point>, Grid<char>);
 
-fn calculate_total_load(input: &str) -> usize {
-    let (mut rounded_rocks, mut cube_shaped_rocks, lines) = parse_rocks(read_lines(input));
+pub fn calculate_total_load(input: &str) -> Result<usize, ParseError> {
+    let (rounded_rocks, cube_shaped_rocks, platform) = parse_rocks(input)?;
 
-    let rounded_rocks = move_rock_north(&mut rounded_rocks, &mut cube_shaped_rocks);
+    let rounded_rocks = move_rock(&rounded_rocks, &cube_shaped_rocks, &platform, Up);
 
-    let dish_len = lines.len();
-
-    total_load(&rounded_rocks, dish_len)
+    Ok(total_load(&rounded_rocks, platform.height()))
 }
 
-fn total_load(rounded_rocks: &HashSet<Position>, dish_len: usize) -> usize {
-    let rounded_rocks_by_y = rounded_rocks.iter().into_group_map_by(|p1| p1.1);
+fn total_load(rounded_rocks: &HashSet<Point>, dish_len: usize) -> usize {
+    let rounded_rocks_by_y = rounded_rocks.iter().into_group_map_by(|p1| p1.y as usize);
     let mut total_load: usize = 0;
     for y in 0..dish_len {
         if let Some(rocks) = rounded_rocks_by_y.get(&y) {
@@ -28,21 +28,16 @@ fn total_load(rounded_rocks: &HashSet<Position>, dish_len: usize) -> usize {
     total_load
 }
 
-fn calculate_total_load_with_cycle(input: &str) -> usize {
-    let (mut rounded_rocks, mut cube_shaped_rocks, lines) = parse_rocks(read_lines(input));
-    let number_of_columns = lines.len();
-    let number_of_rows = lines[0].len();
+pub fn calculate_total_load_with_cycle(input: &str) -> Result<usize, ParseError> {
+    let (mut rounded_rocks, cube_shaped_rocks, platform) = parse_rocks(input)?;
 
-    let mut periods: Vec<HashSet<Position>> = vec![];
+    let mut periods: Vec<HashSet<Point>> = vec![];
 
     let mut cycle: usize = 0;
     let instant: usize;
 
     loop {
-        rounded_rocks = move_rock_north(&mut rounded_rocks, &mut cube_shaped_rocks);
-        rounded_rocks = move_rock_west(&mut rounded_rocks, &mut cube_shaped_rocks);
-        rounded_rocks = move_rock_south(&mut rounded_rocks, &mut cube_shaped_rocks, number_of_columns);
-        rounded_rocks = move_rock_east(&mut rounded_rocks, &mut cube_shaped_rocks, number_of_rows);
+        rounded_rocks = spin_cycle(&rounded_rocks, &cube_shaped_rocks, &platform);
         cycle += 1;
         if let Some((i, _)) = periods.iter().find_position(|&p| *p == rounded_rocks) {
             instant = i + 1;
@@ -57,75 +52,35 @@ fn calculate_total_load_with_cycle(input: &str) -> usize {
     let remaining = (1000000000 - instant) % period;
 
     for _ in 0..remaining {
-        rounded_rocks = move_rock_north(&mut rounded_rocks, &mut cube_shaped_rocks);
-        rounded_rocks = move_rock_west(&mut rounded_rocks, &mut cube_shaped_rocks);
-        rounded_rocks = move_rock_south(&mut rounded_rocks, &mut cube_shaped_rocks, number_of_columns);
-        rounded_rocks = move_rock_east(&mut rounded_rocks, &mut cube_shaped_rocks, number_of_rows);
-    }
-
-    total_load(&rounded_rocks, number_of_columns)
-}
-
-fn parse_rocks(lines: Vec<String>) -> (HashSet<Position>, HashSet<Position>, Vec<String>) {
-    let mut rounded_rocks: HashSet<Position> = HashSet::new();
-    let mut cube_shaped_rocks: HashSet<Position> = HashSet::new();
-    for (y, row) in lines.iter().enumerate() {
-        for (x, c) in row.chars().enumerate() {
-            if c == 'O' {
-                rounded_rocks.insert(Position(x, y));
-            }
-            if c == '#' {
-                cube_shaped_rocks.insert(Position(x, y));
-            }
-        }
+        rounded_rocks = spin_cycle(&rounded_rocks, &cube_shaped_rocks, &platform);
     }
-    (rounded_rocks, cube_shaped_rocks, lines)
-}
 
-fn move_rock_north(rounded_rocks: &HashSet<Position>, cube_shaped_rocks: &HashSet<Position>) -> HashSet<Position> {
-    move_rock(rounded_rocks,
-              cube_shaped_rocks,
-              |Position(_, y)| { *y == 0 },
-              |p: &Position| { Position(p.0, p.1 - 1) })
+    Ok(total_load(&rounded_rocks, platform.height()))
 }
 
-fn move_rock_south(rounded_rocks: &HashSet<Position>, cube_shaped_rocks: &HashSet<Position>, number_of_row: usize) -> HashSet<Position> {
-    move_rock(rounded_rocks,
-              cube_shaped_rocks,
-              |Position(_, y)| { *y == number_of_row.clone() - 1 },
-              |p: &Position| { Position(p.0, p.1 + 1) })
+fn spin_cycle(rounded_rocks: &HashSet<Point>, cube_shaped_rocks: &HashSet<Point>, platform: &Grid<char>) -> HashSet<Point> {
+    [Up, Left, Down, Right].iter()
+        .fold(rounded_rocks.clone(), |rocks, &direction| move_rock(&rocks, cube_shaped_rocks, platform, direction))
 }
 
-fn move_rock_east(rounded_rocks: &HashSet<Position>, cube_shaped_rocks: &HashSet<Position>, number_of_columns: usize) -> HashSet<Position> {
-    move_rock(rounded_rocks,
-              cube_shaped_rocks,
-              |Position(x, _)| { *x == number_of_columns - 1 },
-              |p: &Position| { Position(p.0 + 1, p.1) })
-}
+fn parse_rocks(input: &str) -> Result<Platform, ParseError> {
+    let platform = Grid::parse(14, input, "O#.")?;
+    let rocks = |rock: char| platform.iter().filter(|(_, &c)| c == rock).map(|(p, _)| p).collect::<HashSet<_>>();
 
-fn move_rock_west(rounded_rocks: &HashSet<Position>, cube_shaped_rocks: &HashSet<Position>) -> HashSet<Position> {
-    move_rock(rounded_rocks,
-              cube_shaped_rocks,
-              |Position(x, _)| { *x == 0 },
-              |p: &Position| { Position(p.0 - 1, p.1) })
+    Ok((rocks('O'), rocks('#'), platform))
 }
 
-fn move_rock(rounded_rocks: &HashSet<Position>,
-             cube_shaped_rocks: &HashSet<Position>,
-             is_invalid_rock: impl Fn(&Position)-> bool,
-             increment: fn(&Position) -> Position) -> HashSet<Position> {
+fn move_rock(rounded_rocks: &HashSet<Point>,
+             cube_shaped_rocks: &HashSet<Point>,
+             platform: &Grid<char>,
+             direction: Direction) -> HashSet<Point> {
     let mut rounded_rocks = rounded_rocks.clone();
     loop {
-        let mut next_rounded_rocks: HashSet<Position> = HashSet::new();
-        for Position(x, y) in &rounded_rocks {
-            if is_invalid_rock(&Position(*x, *y)) {
-                next_rounded_rocks.insert(Position(*x, *y));
-                continue;
-            }
-
-            let next_position = increment(&Position(*x, *y));
-            if rounded_rocks.contains(&next_position) || cube_shaped_rocks.contains(&next_position) {
-                next_rounded_rocks.insert(Position(*x, *y));
+        let mut next_rounded_rocks: HashSet<Point> = HashSet::new();
+        for &rock in &rounded_rocks {
+            let next_position = rock.step(direction);
+            if !platform.contains(next_position) || rounded_rocks.contains(&next_position) || cube_shaped_rocks.contains(&next_position) {
+                next_rounded_rocks.insert(rock);
             } else {
                 next_rounded_rocks.insert(next_position);
             }
@@ -141,6 +96,22 @@ fn move_rock(rounded_rocks: &HashSet<Position>,
     rounded_rocks
 }
 
+pub struct Day14;
+
+impl Solution for Day14 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_rocks(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_total_load(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_total_load_with_cycle(input)?.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -152,7 +123,7 @@ mod tests {
     fn it_solves_first_part() {
         let input = &read_input_file("input_day14.txt");
 
-        assert_eq!(108826, calculate_total_load(input));
+        assert_eq!(Ok(108826), calculate_total_load(input));
     }
 
     #[test]
@@ -160,7 +131,7 @@ mod tests {
     fn it_solves_second_part() {
         let input = &read_input_file("input_day14.txt");
 
-        assert_eq!(99291, calculate_total_load_with_cycle(input));
+        assert_eq!(Ok(99291), calculate_total_load_with_cycle(input));
     }
 
     #[test]
@@ -177,7 +148,7 @@ mod tests {
         #....###..
         #OO..#...."};
 
-        assert_eq!(136, calculate_total_load(input));
+        assert_eq!(Ok(136), calculate_total_load(input));
     }
 
     #[test]
@@ -194,6 +165,6 @@ mod tests {
         #....###..
         #OO..#...."};
 
-        assert_eq!(64, calculate_total_load_with_cycle(input));
+        assert_eq!(Ok(64), calculate_total_load_with_cycle(input));
     }
 }
\ No newline at end of file
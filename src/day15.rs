@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 use itertools::Itertools;
 
-fn calculate_initialization_sequence_hash_sum(row: &str) -> usize {
+use crate::parse_error::{Line, lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
+
+pub fn calculate_initialization_sequence_hash_sum(row: &str) -> usize {
     row.split(",").map(calculate_hash).sum::<usize>()
 }
 
-fn calculate_hash(step: &str) -> usize {
+pub fn calculate_hash(step: &str) -> usize {
     let mut hash: usize = 0;
 
     for c in step.chars() {
@@ -16,35 +19,35 @@ fn calculate_hash(step: &str) -> usize {
     hash
 }
 
-fn calculate_focusing_power(row: &str) -> usize {
-    let raw_instructions = row.split(",").collect::<Vec<_>>();
+pub fn calculate_focusing_power(row: &Line) -> Result<usize, ParseError> {
+    let raw_instructions = row.text.split(',').collect::<Vec<_>>();
     let mut boxes: HashMap<usize, Vec<&str>> = HashMap::new();
     let mut lens_value: HashMap<&str, usize> = HashMap::new();
 
     for instruction in raw_instructions {
-        if instruction.contains('=') {
-            let parts = instruction.split('=').collect::<Vec<_>>();
-            let box_position = calculate_hash(parts[0]);
-            let b = boxes.entry(box_position).or_insert(Vec::new());
-            if !b.contains(&parts[0]) {
-                b.push(&parts[0]);
+        if let Some((label, value)) = instruction.split_once('=') {
+            let box_position = calculate_hash(label);
+            let b = boxes.entry(box_position).or_default();
+            if !b.contains(&label) {
+                b.push(label);
             }
-            let value = parts[1].parse::<usize>().unwrap();
-            *lens_value.entry(&parts[0]).or_insert(value) = value;
-        } else {
-            let parts = instruction.split('-').collect::<Vec<_>>();
-            let box_position = calculate_hash(parts[0]);
-            let b = boxes.entry(box_position).or_insert(Vec::new());
-            if let Some((index, _)) = b.iter().find_position(|&c| *c == parts[0]) {
+            let value = row.parse::<usize>(value)?;
+            *lens_value.entry(label).or_insert(value) = value;
+        } else if let Some(label) = instruction.strip_suffix('-') {
+            let box_position = calculate_hash(label);
+            let b = boxes.entry(box_position).or_default();
+            if let Some((index, _)) = b.iter().find_position(|&c| *c == label) {
                 b.remove(index);
-                lens_value.remove(&parts[0]);
+                lens_value.remove(&label);
             }
+        } else {
+            return Err(row.error(instruction, format!("expected `<label>=<focal length>` or `<label>-`, found `{}`", instruction)));
         }
     }
 
     let mut sum: usize = 0;
     for box_index in 0..256 {
-        if boxes.get(&box_index) == None {
+        if !boxes.contains_key(&box_index) {
             continue;
         }
 
@@ -53,13 +56,35 @@ fn calculate_focusing_power(row: &str) -> usize {
         }
     }
 
-    sum
+    Ok(sum)
+}
+
+pub fn initialization_sequence(input: &str) -> Result<Line<'_>, ParseError> {
+    lines(15, input).next()
+        .ok_or_else(|| ParseError::end_of_input(15, 1, "an initialization sequence"))
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(initialization_sequence(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_initialization_sequence_hash_sum(initialization_sequence(input)?.text).into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_focusing_power(&initialization_sequence(input)?)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::day15::*;
     use crate::input_reader::{read_input_file, read_lines};
+    use crate::parse_error::Line;
 
     #[test]
     fn it_solves_first_part() {
@@ -72,14 +97,24 @@ mod tests {
     fn it_solves_second_part() {
         let input = read_lines(&read_input_file("input_day15.txt"));
 
-        assert_eq!(284132, calculate_focusing_power(&input[0]));
+        assert_eq!(Ok(284132), calculate_focusing_power(&Line::new(15, 1, &input[0])));
     }
 
     #[test]
     fn it_calculates_focusing_power() {
         let row = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
-        assert_eq!(145, calculate_focusing_power(row));
+        assert_eq!(Ok(145), calculate_focusing_power(&Line::new(15, 1, row)));
+    }
+
+    #[test]
+    fn it_reports_a_malformed_step() {
+        let row = "rn=1,cm-,qp+3";
+
+        assert_eq!(
+            Err(ParseError::new(15, 1, 10, "expected `<label>=<focal length>` or `<label>-`, found `qp+3`")),
+            calculate_focusing_power(&Line::new(15, 1, row))
+        );
     }
 
     #[test]
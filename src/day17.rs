@@ -1,173 +1,129 @@
-use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
-
-use crate::day17::Direction::*;
-use crate::input_reader::read_lines;
-
-#[derive(Debug, Hash, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+use crate::grid::{Direction, Grid, Point};
+use crate::grid::Direction::*;
+use crate::parse_error::ParseError;
+use crate::search::dijkstra;
+use crate::solution::{Answer, Solution, SolveError};
+
+// `steps` counts the blocks moved in a straight line to reach `position`
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct State {
+    pub position: Point,
+    pub steps: usize,
+    pub direction: Direction,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    heat_lost: usize,
-    position: (usize, usize),
-    steps: usize,
-    direction: Direction,
+// the states from the starting block to the target, both included
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Route {
+    pub heat_loss: usize,
+    pub states: Vec<State>,
 }
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.heat_lost.cmp(&self.heat_lost)
-    }
+// how a vehicle moves: it goes straight for at least `min_straight` blocks before turning or stopping,
+// and for at most `max_straight` blocks. Start and target default to the top left and bottom right blocks.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct CrucibleSpec {
+    pub min_straight: usize,
+    pub max_straight: usize,
+    pub can_reverse: bool,
+    pub start: Option<Point>,
+    pub target: Option<Point>,
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
+impl CrucibleSpec {
+    pub const CRUCIBLE: CrucibleSpec = CrucibleSpec { min_straight: 0, max_straight: 3, can_reverse: false, start: None, target: None };
+    pub const ULTRA_CRUCIBLE: CrucibleSpec = CrucibleSpec { min_straight: 4, max_straight: 10, ..CrucibleSpec::CRUCIBLE };
 }
 
-struct CityMap {
-    city_blocks: Vec<Vec<usize>>,
-    length: usize,
-    width: usize,
-    neighbours_finder: fn(&CityMap, &State) -> Vec<State>,
-    end_condition: fn((usize, usize), &State) -> bool,
+pub struct CityMap {
+    city_blocks: Grid<usize>,
+    spec: CrucibleSpec,
 }
 
 impl CityMap {
-    fn build(input: &str) -> Self {
-        Self::new(
-            input,
-            |map, current_state| find_neighbours(map, current_state, 0, 3),
-            |target, current| target == current.position,
-        )
+    pub fn build(input: &str) -> Result<Self, ParseError> {
+        Self::new(input, CrucibleSpec::CRUCIBLE)
     }
 
-    fn build_for_crucibles(input: &str) -> Self {
-        Self::new(
-            input,
-            |map, current_state| find_neighbours(map, current_state, 4, 10),
-            |target, current| target == current.position && current.steps >= 4,
-        )
+    pub fn build_for_crucibles(input: &str) -> Result<Self, ParseError> {
+        Self::new(input, CrucibleSpec::ULTRA_CRUCIBLE)
     }
 
-    fn new(input: &str,
-           finder: fn(&CityMap, &State) -> Vec<State>,
-           end_condition: fn((usize, usize), &State) -> bool) -> Self {
-        let mut city_blocks: Vec<Vec<usize>> = vec![];
-        for row in read_lines(input) {
-            let mut parsed_row: Vec<usize> = vec![];
-            for c in row.chars() {
-                parsed_row.push(c.to_digit(10).unwrap() as usize);
-            }
-            city_blocks.push(parsed_row);
-        }
+    pub fn new(input: &str, spec: CrucibleSpec) -> Result<Self, ParseError> {
+        let city_blocks = Grid::parse(17, input, "0123456789")?
+            .map(|c| c.to_digit(10).unwrap() as usize);
 
-        CityMap {
-            city_blocks: city_blocks.clone(),
-            length: city_blocks.len(),
-            width: city_blocks[0].len(),
-            neighbours_finder: finder,
-            end_condition,
-        }
+        Ok(CityMap { city_blocks, spec })
     }
 
-    fn initial_heat_losses(&self) -> HashMap<(usize, usize, Direction, usize), usize> {
-        let mut distances = HashMap::new();
-
-        for y in 0..self.city_blocks.len() {
-            for x in 0..self.city_blocks[y].len() {
-                for steps in 1..=10 {
-                    distances.insert((x, y, Up, steps), usize::MAX);
-                    distances.insert((x, y, Down, steps), usize::MAX);
-                    distances.insert((x, y, Left, steps), usize::MAX);
-                    distances.insert((x, y, Right, steps), usize::MAX);
-                }
-            }
-        }
-
-        distances
+    fn start(&self) -> Point {
+        self.spec.start.unwrap_or_default()
     }
 
-    fn target(&self) -> (usize, usize) {
-        (self.width - 1, self.length - 1)
+    fn target(&self) -> Point {
+        self.spec.target
+            .unwrap_or_else(|| Point::from((self.city_blocks.width() - 1, self.city_blocks.height() - 1)))
     }
 
-    fn heat_at(&self, x: usize, y: usize) -> usize {
-        self.city_blocks[y][x]
+    fn heat_at(&self, position: Point) -> usize {
+        self.city_blocks[position]
     }
 
-    fn minimize_heat_loss(&self) -> usize {
+    pub fn minimize_heat_loss(&self) -> Option<Route> {
         let target = self.target();
-        let mut heat_losses = self.initial_heat_losses();
-
-        let mut heap: BinaryHeap<State> = BinaryHeap::new();
-
-        heap.push(State { heat_lost: self.heat_at(1, 0), position: (1, 0), steps: 1, direction: Right });
-        heap.push(State { heat_lost: self.heat_at(0, 1), position: (0, 1), steps: 1, direction: Down });
-
-        while let Some(state) = heap.pop() {
-            if (self.end_condition)(target, &state) {
-                return state.heat_lost;
-            }
+        if !self.city_blocks.contains(self.start()) || !self.city_blocks.contains(target) {
+            return None;
+        }
 
-            if state.heat_lost > heat_losses[&(state.position.0, state.position.1, state.direction, state.steps)] {
-                continue;
-            }
+        // the crucible has not moved yet, it can leave the start in any direction
+        let starts = Direction::ALL.map(|direction| State { position: self.start(), steps: 0, direction });
 
-            for next_state in (self.neighbours_finder)(self, &state) {
-                if next_state.heat_lost >= heat_losses[&(next_state.position.0, next_state.position.1, next_state.direction, next_state.steps)] {
-                    continue;
-                }
+        // the straight run is bounded by the spec, so is the number of states explored
+        dijkstra(
+            starts,
+            |state| find_neighbours(self, state).into_iter().map(|next| (next, self.heat_at(next.position))),
+            |state| state.position == target && state.steps >= self.spec.min_straight,
+        ).map(|found| Route { heat_loss: found.cost, states: found.path })
+    }
 
-                *heat_losses.get_mut(&(next_state.position.0, next_state.position.1, next_state.direction, next_state.steps)).unwrap() = next_state.heat_lost;
-                heap.push(next_state);
-            }
+    // draws the route on the city blocks as the puzzle does, the starting block keeps its heat loss
+    pub fn render(&self, route: &Route) -> String {
+        let mut drawing = self.city_blocks.map(|&heat| char::from_digit(heat as u32, 10).unwrap());
+        for state in route.states.iter().skip(1) {
+            drawing[state.position] = state.direction.arrow();
         }
-
-        panic!("NOT FOUND!")
+        drawing.to_string()
     }
 }
 
-fn find_neighbours(map: &CityMap, current_state: &State, min_consecutive_steps: usize, max_consecutive_steps: usize) -> Vec<State> {
+fn minimize_heat_loss(map: CityMap) -> Result<usize, SolveError> {
+    map.minimize_heat_loss()
+        .map(|route| route.heat_loss)
+        .ok_or_else(|| SolveError::unsolvable(17, "no route reaches the bottom right city block"))
+}
+
+fn find_neighbours(map: &CityMap, current_state: &State) -> Vec<State> {
+    let spec = &map.spec;
     let mut possible_neighbours = vec![];
 
     for direction in [Right, Left, Up, Down] {
-        if current_state.direction != direction && current_state.steps < min_consecutive_steps {
-            continue;
-        }
-
-        if current_state.direction == Up && direction == Down {
-            continue;
-        }
-
-        if current_state.direction == Down && direction == Up {
+        if current_state.direction != direction && current_state.steps < spec.min_straight {
             continue;
         }
 
-        if current_state.direction == Left && direction == Right {
+        if direction == current_state.direction.opposite() && !spec.can_reverse {
             continue;
         }
 
-        if current_state.direction == Right && direction == Left {
+        if current_state.steps == spec.max_straight && direction == current_state.direction {
             continue;
         }
 
-        if current_state.steps == max_consecutive_steps && direction == current_state.direction {
-            continue;
-        }
-
-        if let Some(new_position) = find_new_position(map, direction, current_state.position) {
+        let new_position = current_state.position.step(direction);
+        if map.city_blocks.contains(new_position) {
             possible_neighbours.push(
                 State {
                     steps: if direction == current_state.direction { current_state.steps + 1 } else { 1 },
-                    heat_lost: current_state.heat_lost + map.heat_at(new_position.0, new_position.1),
                     position: new_position,
                     direction,
                 }
@@ -178,24 +134,20 @@ fn find_neighbours(map: &CityMap, current_state: &State, min_consecutive_steps:
     possible_neighbours
 }
 
-fn find_new_position(map: &CityMap, direction: Direction, position: (usize, usize)) -> Option<(usize, usize)> {
-    if direction == Up && position.1 > 0 {
-        return Some((position.0, position.1 - 1));
-    }
+pub struct Day17;
 
-    if direction == Down && position.1 < map.length - 1 {
-        return Some((position.0, position.1 + 1));
+impl Solution for Day17 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(CityMap::build(input).map(|_| ()))
     }
 
-    if direction == Left && position.0 > 0 {
-        return Some((position.0 - 1, position.1));
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(minimize_heat_loss(CityMap::build(input)?)?.into())
     }
 
-    if direction == Right && position.0 < map.width - 1 {
-        return Some((position.0 + 1, position.1));
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(minimize_heat_loss(CityMap::build_for_crucibles(input)?)?.into())
     }
-
-    None
 }
 
 #[cfg(test)]
@@ -210,7 +162,7 @@ mod tests {
     fn it_solves_first_part() {
         let input = &read_input_file("input_day17.txt");
 
-        assert_eq!(1138, CityMap::build(input).minimize_heat_loss())
+        assert_eq!(1138, CityMap::build(input).unwrap().minimize_heat_loss().unwrap().heat_loss)
     }
 
     #[test]
@@ -218,7 +170,7 @@ mod tests {
     fn it_solves_second_part() {
         let input = &read_input_file("input_day17.txt");
 
-        assert_eq!(1312, CityMap::build_for_crucibles(input).minimize_heat_loss())
+        assert_eq!(1312, CityMap::build_for_crucibles(input).unwrap().minimize_heat_loss().unwrap().heat_loss)
     }
 
     #[test]
@@ -238,7 +190,7 @@ mod tests {
         2546548887735
         4322674655533"};
 
-        assert_eq!(102, CityMap::build(input).minimize_heat_loss())
+        assert_eq!(102, CityMap::build(input).unwrap().minimize_heat_loss().unwrap().heat_loss)
     }
 
     #[test]
@@ -258,7 +210,7 @@ mod tests {
         2546548887735
         4322674655533"};
 
-        assert_eq!(94, CityMap::build_for_crucibles(input).minimize_heat_loss());
+        assert_eq!(94, CityMap::build_for_crucibles(input).unwrap().minimize_heat_loss().unwrap().heat_loss);
 
         let input = indoc! {"
         111111111111
@@ -266,6 +218,73 @@ mod tests {
         999999999991
         999999999991
         999999999991"};
-        assert_eq!(71, CityMap::build_for_crucibles(input).minimize_heat_loss());
+        assert_eq!(71, CityMap::build_for_crucibles(input).unwrap().minimize_heat_loss().unwrap().heat_loss);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_renders_the_route() {
+        let input = indoc! {"
+        2413432311323
+        3215453535623
+        3255245654254
+        3446585845452
+        4546657867536
+        1438598798454
+        4457876987766
+        3637877979653
+        4654967986887
+        4564679986453
+        1224686865563
+        2546548887735
+        4322674655533"};
+
+        let map = CityMap::build(input).unwrap();
+        let route = map.minimize_heat_loss().unwrap();
+        let expected = indoc! {"
+        2>>34^>>>1323
+        32v>>>35v5623
+        32552456v>>54
+        3446585845v52
+        4546657867v>6
+        14385987984v4
+        44578769877v6
+        36378779796v>
+        465496798688v
+        456467998645v
+        12246868655<v
+        25465488877v5
+        43226746555v>"};
+        assert_eq!(expected, map.render(&route));
+        assert_eq!(102, route.heat_loss);
+        assert_eq!((Point::new(0, 0), 0), (route.states[0].position, route.states[0].steps));
+    }
+
+    #[test]
+    fn it_follows_the_crucible_spec() {
+        let long_street = "1111111111111111";
+        assert_eq!(None, CityMap::build(long_street).unwrap().minimize_heat_loss());
+
+        let spec = CrucibleSpec { max_straight: 15, ..CrucibleSpec::CRUCIBLE };
+        assert_eq!(15, CityMap::new(long_street, spec).unwrap().minimize_heat_loss().unwrap().heat_loss);
+
+        let spec = CrucibleSpec { min_straight: 1, can_reverse: true, target: Some(Point::new(0, 0)), ..CrucibleSpec::CRUCIBLE };
+        let map = CityMap::new("12345", spec).unwrap();
+        let route = map.minimize_heat_loss().unwrap();
+        assert_eq!(3, route.heat_loss);
+        assert_eq!("<>345", map.render(&route));
+        assert_eq!(None, CityMap::new("12345", CrucibleSpec { can_reverse: false, ..spec }).unwrap().minimize_heat_loss());
+
+        let spec = CrucibleSpec { start: Some(Point::new(4, 0)), target: Some(Point::new(1, 0)), ..CrucibleSpec::CRUCIBLE };
+        assert_eq!(9, CityMap::new("12345", spec).unwrap().minimize_heat_loss().unwrap().heat_loss);
+    }
+
+    #[test]
+    fn it_finds_no_route() {
+        let input = indoc! {"
+        111
+        111"};
+
+        assert_eq!(None, CityMap::build_for_crucibles(input).unwrap().minimize_heat_loss());
+        assert_eq!(Err(SolveError::unsolvable(17, "no route reaches the bottom right city block")), Day17.part2(input));
+    }
+}
@@ -1,28 +1,29 @@
 use std::ops::Div;
 
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, lines, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
-fn calculate_cubic_meters_of_lava(input: &str, digger: &mut Digger) -> isize {
-    digger.find_tranches(input);
-    digger.perimeter + number_of_internal_tranches(&digger.polygon, digger.perimeter)
+pub fn calculate_cubic_meters_of_lava(input: &str, digger: &mut Digger) -> Result<isize, ParseError> {
+    digger.find_tranches(input)?;
+    Ok(digger.perimeter + number_of_internal_tranches(&digger.polygon, digger.perimeter))
 }
 
 // Calculate number of internal tranches using Pick's theorem
 // https://en.wikipedia.org/wiki/Pick's_theorem
-fn number_of_internal_tranches(polygon: &Vec<Position>, perimeter: isize) -> isize {
-    let area = calculate_area(&polygon);
+fn number_of_internal_tranches(polygon: &[Position], perimeter: isize) -> isize {
+    let area = calculate_area(polygon);
     let b = perimeter;
     area + 1 - (b / 2)
 }
 
 // Calculate area using the Shoelace formula
 // https://en.wikipedia.org/wiki/Shoelace_formula
-fn calculate_area(polygon: &Vec<Position>) -> isize {
+fn calculate_area(polygon: &[Position]) -> isize {
     polygon.windows(2)
         .map(|positions| {
             let Position(x1, y1) = positions[0];
             let Position(x2, y2) = positions[1];
-            (x1 * y2) as isize - (x2 * y1) as isize
+            (x1 * y2) - (x2 * y1)
         })
         .sum::<isize>()
         .abs()
@@ -32,15 +33,15 @@ fn calculate_area(polygon: &Vec<Position>) -> isize {
 #[derive(Debug, Hash, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
 struct Position(isize, isize);
 
-struct Digger {
+pub struct Digger {
     current_position: Position,
     perimeter: isize,
     polygon: Vec<Position>,
-    instruction_parser: fn(String) -> (String, isize)
+    instruction_parser: fn(&Line) -> Result<(String, isize), ParseError>
 }
 
 impl Digger {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Digger {
             current_position: Position(0, 0),
             perimeter: 0,
@@ -49,7 +50,7 @@ impl Digger {
         }
     }
 
-    fn build_with_color_instruction() -> Self {
+    pub fn build_with_color_instruction() -> Self {
         Digger {
             current_position: Position(0, 0),
             perimeter: 0,
@@ -58,14 +59,15 @@ impl Digger {
         }
     }
 
-    fn find_tranches(&mut self, input: &str) {
-        for line in read_lines(input) {
-            let(direction, steps) = (self.instruction_parser)(line);
+    fn find_tranches(&mut self, input: &str) -> Result<(), ParseError> {
+        for line in lines(18, input) {
+            let(direction, steps) = (self.instruction_parser)(&line)?;
             self.perimeter += steps;
             let next_position = self.dig(&direction, steps);
-            self.current_position = next_position.clone();
-            self.polygon.push(self.current_position.clone());
+            self.current_position = next_position;
+            self.polygon.push(self.current_position);
         }
+        Ok(())
     }
 
     fn dig(&mut self, direction: &str, steps: isize) -> Position {
@@ -79,28 +81,54 @@ impl Digger {
     }
 }
 
-fn parse_instruction(raw_instruction: String) -> (String, isize) {
-    let line_parts = raw_instruction.split(" (").collect::<Vec<_>>();
-    let dig_instruction = line_parts[0];
-    let instruction_parts = dig_instruction.split(" ").collect::<Vec<_>>();
-    let direction = instruction_parts[0];
-    let steps = instruction_parts[1].parse::<isize>().unwrap();
-    (direction.to_string(), steps)
+impl Default for Digger {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-fn parse_instruction_color(raw_instruction: String) -> (String, isize) {
-    let line_parts = raw_instruction.split(" (").collect::<Vec<_>>();
-    let color = line_parts[1].split(")").collect::<Vec<_>>()[0].chars().collect::<Vec<_>>();
-    let direction = match color.last().unwrap() {
-        '0' => "R",
-        '1' => "D",
-        '2' => "L",
-        '3' => "U",
-        _ => panic!("unexpected direction")
+fn parse_instruction(raw_instruction: &Line) -> Result<(String, isize), ParseError> {
+    let (dig_instruction, _) = raw_instruction.split_once(raw_instruction.text, " (")?;
+    let (direction, steps) = raw_instruction.split_once(dig_instruction, " ")?;
+    if !["U", "D", "R", "L"].contains(&direction) {
+        return Err(raw_instruction.error(direction, format!("unexpected direction `{}`", direction)));
+    }
+    let steps = raw_instruction.parse::<isize>(steps)?;
+    Ok((direction.to_string(), steps))
+}
+
+fn parse_instruction_color(raw_instruction: &Line) -> Result<(String, isize), ParseError> {
+    let (_, color) = raw_instruction.split_once(raw_instruction.text, " (")?;
+    let hex = color.strip_prefix('#')
+        .and_then(|c| c.strip_suffix(')'))
+        .filter(|c| c.len() == 6 && c.is_ascii())
+        .ok_or_else(|| raw_instruction.error(color, format!("expected a color like `#70c710)`, found `{}`", color)))?;
+    let direction = match &hex[5..] {
+        "0" => "R",
+        "1" => "D",
+        "2" => "L",
+        "3" => "U",
+        unexpected => return Err(raw_instruction.error(&hex[5..], format!("unexpected direction `{}`", unexpected)))
     };
-    let hex = color[1..color.len() - 1].iter().collect::<String>();
-    let steps = isize::from_str_radix(&hex, 16).unwrap();
-    (direction.to_string(), steps)
+    let steps = isize::from_str_radix(&hex[..5], 16)
+        .map_err(|_| raw_instruction.error(hex, format!("expected a hexadecimal distance, found `{}`", &hex[..5])))?;
+    Ok((direction.to_string(), steps))
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_lines(18, input, parse_instruction).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_cubic_meters_of_lava(input, &mut Digger::new())?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_cubic_meters_of_lava(input, &mut Digger::build_with_color_instruction())?.into())
+    }
 }
 
 #[cfg(test)]
@@ -114,8 +142,8 @@ mod tests {
     fn it_solves_puzzle() {
         let input = &read_input_file("input_day18.txt");
 
-        assert_eq!(45159, calculate_cubic_meters_of_lava(input, &mut Digger::new()));
-        assert_eq!(134549294799713, calculate_cubic_meters_of_lava(input, &mut Digger::build_with_color_instruction()));
+        assert_eq!(Ok(45159), calculate_cubic_meters_of_lava(input, &mut Digger::new()));
+        assert_eq!(Ok(134549294799713), calculate_cubic_meters_of_lava(input, &mut Digger::build_with_color_instruction()));
     }
 
     #[test]
@@ -136,12 +164,22 @@ mod tests {
         L 2 (#015232)
         U 2 (#7a21e3)"};
 
-        assert_eq!(62, calculate_cubic_meters_of_lava(input, &mut Digger::new()));
-        assert_eq!(952408144115, calculate_cubic_meters_of_lava(input, &mut Digger::build_with_color_instruction()));
+        assert_eq!(Ok(62), calculate_cubic_meters_of_lava(input, &mut Digger::new()));
+        assert_eq!(Ok(952408144115), calculate_cubic_meters_of_lava(input, &mut Digger::build_with_color_instruction()));
     }
 
     #[test]
     fn it_parses_color_instruction() {
-        assert_eq!(("R".to_string(), 461937), parse_instruction_color("R 6 (#70c710)".to_string()))
+        assert_eq!(Ok(("R".to_string(), 461937)), parse_instruction_color(&Line::new(18, 1, "R 6 (#70c710)")));
+        assert_eq!(Err(ParseError::new(18, 1, 12, "unexpected direction `7`")), parse_instruction_color(&Line::new(18, 1, "R 6 (#70c717)")));
+    }
+
+    #[test]
+    fn it_reports_an_unexpected_direction() {
+        let input = indoc! {"
+        R 6 (#70c710)
+        X 5 (#0dc571)"};
+
+        assert_eq!(Err(ParseError::new(18, 2, 1, "unexpected direction `X`")), calculate_cubic_meters_of_lava(input, &mut Digger::new()));
     }
 }
\ No newline at end of file
@@ -1,451 +1,1159 @@
-#[cfg(test)]
-mod tests {
-    use std::cmp::{max, min};
-    use std::collections::{HashMap, LinkedList};
-
-    use indoc::indoc;
-    use regex::Regex;
+use std::cmp::{max, min, Ordering};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use regex::Regex;
+
+use crate::benchmark::fastest;
+use crate::parse_error::{Line, lines, ParseError};
+use crate::search::reachable;
+use crate::solution::{Answer, Solution, SolveError};
+
+// the attributes rated in the puzzle
+pub const XMAS: [&str; 4] = ["x", "m", "a", "s"];
+
+pub type Part = HashMap<String, usize>;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Verdict {
+    Accepted,
+    Rejected,
+}
+
+// the workflows a part went through, from `in` to the one accepting or rejecting it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Evaluation {
+    pub verdict: Verdict,
+    pub chain: Vec<String>,
+}
+
+pub struct WorkflowSet {
+    workflows: HashMap<String, Workflow>,
+}
+
+impl WorkflowSet {
+    // reads the workflows at the top of the input, the parts after the empty line are ignored.
+    // Workflows going to undefined workflows, or going round in circles, are reported.
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        WorkflowSet::parse_with(input, &XMAS)
+    }
 
-    use crate::input_reader::{read_input_file, read_lines};
+    // rules may only compare the given attributes
+    pub fn parse_with(input: &str, attributes: &[&str]) -> Result<Self, ParseError> {
+        let (raw_workflows, _) = split_input(input);
+        let workflows = WorkflowSet::new(parse_workflows(&raw_workflows, attributes, true)?);
 
-    #[test]
-    fn it_solves_first_part() {
-        let input = &read_input_file("input_day19.txt");
+        if let Some(cycle) = workflows.cycles().first() {
+            let names = cycle.iter().chain(cycle.first()).cloned().collect::<Vec<_>>().join(" -> ");
+            return Err(ParseError::new(19, workflows.workflows[&cycle[0]].line, 1, format!("workflows `{}` form a cycle", names)));
+        }
+        Ok(workflows)
+    }
 
-        assert_eq!(476889, calculate_sum_accepted_ratings(input));
+    // only the syntax of the workflows is checked, the set is meant to be validated before being evaluated
+    pub fn parse_unchecked(input: &str) -> Result<Self, ParseError> {
+        WorkflowSet::parse_unchecked_with(input, &XMAS)
     }
 
-    #[test]
-    fn it_solves_second_part() {
-        let input = &read_input_file("input_day19.txt");
+    pub fn parse_unchecked_with(input: &str, attributes: &[&str]) -> Result<Self, ParseError> {
+        let (raw_workflows, _) = split_input(input);
+        Ok(WorkflowSet::new(parse_workflows(&raw_workflows, attributes, false)?))
+    }
 
-        assert_eq!(132380153677887, calculate_distinct_combinations(input));
+    fn new(workflows: HashMap<&str, Workflow>) -> Self {
+        WorkflowSet { workflows: workflows.into_iter().map(|(name, workflow)| (name.to_string(), workflow)).collect() }
     }
 
-    #[test]
-    fn it_calculates_sum_accepted_ratings() {
-        let input = indoc! {"
-        px{a<2006:qkq,m>2090:A,rfg}
-        pv{a>1716:R,A}
-        lnx{m>1548:A,A}
-        rfg{s<537:gd,x>2440:R,A}
-        qs{s>3448:A,lnx}
-        qkq{x<1416:A,crn}
-        crn{x>2662:A,R}
-        in{s<1351:px,qqz}
-        qqz{s>2770:qs,m<1801:hdj,R}
-        gd{a>3333:R,R}
-        hdj{m>838:A,pv}
+    pub fn workflow(&self, name: &str) -> Option<&Workflow> {
+        self.workflows.get(name)
+    }
 
-        {x=787,m=2655,a=1222,s=2876}
-        {x=1679,m=44,a=2067,s=496}
-        {x=2036,m=264,a=79,s=2244}
-        {x=2461,m=1339,a=466,s=291}
-        {x=2127,m=1623,a=2188,s=1013}"};
+    // like `evaluate`, without keeping track of the chain
+    pub fn verdict(&self, part: &Part) -> Result<Verdict, String> {
+        let mut workflow = "in";
+        for _ in 0..=self.workflows.len() {
+            match self.known_workflow(workflow)?.execute(part) {
+                "A" => return Ok(Verdict::Accepted),
+                "R" => return Ok(Verdict::Rejected),
+                next => workflow = next
+            }
+        }
+        Err(format!("workflow `{}` leads back to itself", workflow))
+    }
 
-        assert_eq!(19114, calculate_sum_accepted_ratings(input));
+    // fails when the part reaches an undefined workflow, or goes through more workflows than there are,
+    // which only happens with sets from `parse_unchecked`
+    pub fn evaluate(&self, part: &Part) -> Result<Evaluation, String> {
+        let mut chain = vec!["in".to_string()];
+        while chain.len() <= self.workflows.len() + 1 {
+            match self.known_workflow(chain.last().unwrap())?.execute(part) {
+                "A" => return Ok(Evaluation { verdict: Verdict::Accepted, chain }),
+                "R" => return Ok(Evaluation { verdict: Verdict::Rejected, chain }),
+                next => chain.push(next.to_string())
+            }
+        }
+        Err(format!("workflow `{}` leads back to itself", chain.last().unwrap()))
     }
 
-    #[test]
-    fn it_calculates_distinct_combinations() {
-        let input = indoc! {"
-        px{a<2006:qkq,m>2090:A,rfg}
-        pv{a>1716:R,A}
-        lnx{m>1548:A,A}
-        rfg{s<537:gd,x>2440:R,A}
-        qs{s>3448:A,lnx}
-        qkq{x<1416:A,crn}
-        crn{x>2662:A,R}
-        in{s<1351:px,qqz}
-        qqz{s>2770:qs,m<1801:hdj,R}
-        gd{a>3333:R,R}
-        hdj{m>838:A,pv}
+    fn known_workflow(&self, name: &str) -> Result<&Workflow, String> {
+        self.workflows.get(name).ok_or_else(|| format!("unknown workflow `{}`", name))
+    }
+}
+
+// reads the parts after the empty line
+pub fn parse_parts(input: &str) -> Result<Vec<Part>, ParseError> {
+    parse_parts_with(input, &XMAS)
+}
+
+// every part rates all the attributes, and only them
+pub fn parse_parts_with(input: &str, attributes: &[&str]) -> Result<Vec<Part>, ParseError> {
+    let (_, raw_ratings) = split_input(input);
+    raw_ratings.iter().map(|raw_rating| parse_rating(raw_rating, attributes)).collect()
+}
+
+// the parts whose ratings are within an inclusive range for every attribute
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct HyperRectangle {
+    pub ranges: BTreeMap<String, RangeInclusive<usize>>,
+}
+
+impl HyperRectangle {
+    pub fn new(ranges: &[(&str, RangeInclusive<usize>)]) -> Self {
+        HyperRectangle { ranges: ranges.iter().map(|(name, range)| (name.to_string(), range.clone())).collect() }
+    }
 
-        {x=787,m=2655,a=1222,s=2876}
-        {x=1679,m=44,a=2067,s=496}
-        {x=2036,m=264,a=79,s=2244}
-        {x=2461,m=1339,a=466,s=291}
-        {x=2127,m=1623,a=2188,s=1013}"};
+    // the ratings of the puzzle
+    pub fn xmas() -> Self {
+        HyperRectangle::new(&[("x", 1..=4000), ("m", 1..=4000), ("a", 1..=4000), ("s", 1..=4000)])
+    }
 
-        assert_eq!(167409079868000, calculate_distinct_combinations(input));
+    // how many parts are inside, none when that does not fit
+    pub fn volume(&self) -> Option<u128> {
+        self.ranges.values()
+            .map(|range| if range.is_empty() { 0 } else { (range.end() - range.start()) as u128 + 1 })
+            .try_fold(1u128, |volume, width| volume.checked_mul(width))
     }
 
-    fn calculate_distinct_combinations(input: &str) -> usize {
-        let lines = read_lines(input);
-        let parts = lines.split(|s| s.is_empty()).collect::<Vec<_>>();
-        let raw_workflows = parts[0];
-        let workflows = parse_workflows(raw_workflows);
+    pub fn is_empty(&self) -> bool {
+        self.ranges.values().any(|range| range.is_empty())
+    }
 
-        let mut accepted_rules: Vec<Vec<Box<dyn Rule>>> = vec![];
-        let mut parts: LinkedList<(&str, Vec<Box<dyn Rule>>)> = LinkedList::new();
-        parts.push_back(("in", vec![]));
-        while !parts.is_empty() {
-            let (key, rules) = parts.pop_front().unwrap();
-            let workflow = &workflows[key];
-            let mut rejected_rules: Vec<Box<dyn Rule>> = vec![];
+    pub fn overlaps(&self, other: &HyperRectangle) -> bool {
+        self.ranges.iter().all(|(name, range)| {
+            other.ranges.get(name)
+                .is_some_and(|other| max(range.start(), other.start()) <= min(range.end(), other.end()))
+        })
+    }
 
-            for rule_index in 0..workflow.rules.len() - 1 {
-                let rule = &workflow.rules[rule_index];
-                let mut new_rules: Vec<Box<dyn Rule>> = vec![];
-                for r in &rules {
-                    new_rules.push(r.copy());
+    // the parts where `name` is inside `range`, and the others. Empty rectangles are dropped.
+    // Rules split on a threshold, so `range` always reaches the lowest or the highest value.
+    fn split(&self, name: &str, range: RangeInclusive<usize>) -> (Option<HyperRectangle>, Option<HyperRectangle>) {
+        let Some(current) = self.ranges.get(name) else {
+            return (None, Some(self.clone()));
+        };
+
+        let with_range = |new: RangeInclusive<usize>| {
+            let mut rectangle = self.clone();
+            rectangle.ranges.insert(name.to_string(), new);
+            Some(rectangle).filter(|r| !r.is_empty())
+        };
+        let inside = with_range(*max(current.start(), range.start())..=*min(current.end(), range.end()));
+        let rest = if range.start() > current.start() {
+            with_range(*current.start()..=min(*current.end(), range.start() - 1))
+        } else if range.end() < current.end() {
+            with_range(max(*current.start(), range.end() + 1)..=*current.end())
+        } else {
+            None
+        };
+        (inside, rest)
+    }
+}
+
+// the indices of two rectangles sharing some parts, if any
+pub fn find_overlap(rectangles: &[HyperRectangle]) -> Option<(usize, usize)> {
+    (0..rectangles.len())
+        .flat_map(|i| (i + 1..rectangles.len()).map(move |j| (i, j)))
+        .find(|&(i, j)| rectangles[i].overlaps(&rectangles[j]))
+}
+
+impl WorkflowSet {
+    // the rectangles of `domain` accepted by the workflows, they do not overlap.
+    // A rule on an attribute outside of the domain never applies. Fails like `evaluate`.
+    pub fn accepted_rectangles(&self, domain: &HyperRectangle) -> Result<Vec<HyperRectangle>, String> {
+        let mut accepted = vec![];
+        let mut pending = VecDeque::from([("in", domain.clone(), 0)]);
+        while let Some((name, mut rectangle, hops)) = pending.pop_front() {
+            if hops > self.workflows.len() {
+                return Err(format!("workflow `{}` leads back to itself", name));
+            }
+            for rule in self.known_workflow(name)?.rules() {
+                let (matching, rest) = rule.split(&rectangle);
+                if let Some(matching) = matching {
+                    match rule.result() {
+                        "A" => accepted.push(matching),
+                        "R" => {}
+                        next => pending.push_back((next, matching, hops + 1))
+                    }
                 }
 
-                for r in &rejected_rules {
-                    new_rules.push(r.copy());
+                match rest {
+                    Some(rest) => rectangle = rest,
+                    None => break
                 }
+            }
+        }
+        Ok(accepted)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Issue {
+    // there is no `in` workflow, so no part can be rated
+    MissingEntry,
+    UndefinedTarget { workflow: String, target: String },
+    // no part can reach the workflow from `in`
+    Unreachable(String),
+    // the rule at index `rule` never applies, whatever part reaches the workflow
+    ShadowedRule { workflow: String, rule: usize },
+    // the workflows can be followed from the first back to it
+    Cycle(Vec<String>),
+    // every part going through the workflow ends up in `outcome`, the workflow could be a single default
+    SameOutcome { workflow: String, outcome: String },
+}
+
+impl WorkflowSet {
+    // the issues are grouped by kind, in the order of `Issue`, and sorted by workflow.
+    // Rules are checked against the parts in `domain`.
+    pub fn validate(&self, domain: &HyperRectangle) -> Vec<Issue> {
+        let names = self.workflows.keys().sorted().collect::<Vec<_>>();
+        let mut issues = vec![];
+
+        if !self.workflows.contains_key("in") {
+            issues.push(Issue::MissingEntry);
+        }
 
-                new_rules.push(rule.copy());
-                rejected_rules.push(rule.opposite());
-
-                match rule.result() {
-                    "A" => accepted_rules.push(new_rules),
-                    "R" => {}
-                    _ => parts.push_back((rule.result(), new_rules))
+        for &name in &names {
+            for target in self.workflows[name].rules.iter().map(|rule| rule.result()) {
+                if !self.is_defined(target) {
+                    issues.push(Issue::UndefinedTarget { workflow: name.clone(), target: target.to_string() });
                 }
             }
+        }
 
-            let mut new_rules: Vec<Box<dyn Rule>> = vec![];
-            for r in &rules {
-                new_rules.push(r.copy());
+        // without an entry, being unreachable is not specific to any workflow
+        let reachable = reachable(["in"], |&name| self.next_workflows(name, domain), usize::MAX);
+        for &name in names.iter().filter(|_| self.workflows.contains_key("in")) {
+            if !reachable.contains_key(name.as_str()) {
+                issues.push(Issue::Unreachable(name.clone()));
             }
-            for r in &rejected_rules {
-                new_rules.push(r.copy());
+        }
+
+        for &name in &names {
+            for rule in self.shadowed_rules(name, domain) {
+                issues.push(Issue::ShadowedRule { workflow: name.clone(), rule });
             }
+        }
 
-            let result = workflow.rules.last().unwrap().result();
-            match result {
-                "A" => accepted_rules.push(new_rules),
-                "R" => {}
-                _ => parts.push_back((result, new_rules))
+        issues.extend(self.cycles().into_iter().map(Issue::Cycle));
+
+        for &name in &names {
+            if let Some(outcome) = self.same_outcome(name, domain) {
+                issues.push(Issue::SameOutcome { workflow: name.clone(), outcome: outcome.to_string() });
             }
         }
 
-        accepted_rules.iter().map(count_combination_for_instruction).sum()
+        issues
     }
 
-    fn count_combination_for_instruction(instructions: &Vec<Box<dyn Rule>>) -> usize {
-        let mut prod: usize = 1;
-        for rating in "xmas".chars() {
-            let mut rating_min: usize = 1;
-            let mut rating_max: usize = 4000;
+    fn is_defined(&self, target: &str) -> bool {
+        target == "A" || target == "R" || self.workflows.contains_key(target)
+    }
 
-            for instruction in instructions {
-                if instruction.key() != rating.to_string() {
-                    continue;
-                }
+    // the workflows the rules that can apply go to
+    fn next_workflows(&self, name: &str, domain: &HyperRectangle) -> Vec<&str> {
+        let Some(workflow) = self.workflows.get(name) else { return vec![] };
+        let shadowed = self.shadowed_rules(name, domain);
+        workflow.rules.iter().enumerate()
+            .filter(|(index, rule)| !shadowed.contains(index) && self.workflows.contains_key(rule.result()))
+            .map(|(_, rule)| rule.result())
+            .collect()
+    }
 
-                instruction.update(&mut rating_max, &mut rating_min);
+    // the rules that apply to no part of the domain once the previous ones have been tried
+    fn shadowed_rules(&self, name: &str, domain: &HyperRectangle) -> Vec<usize> {
+        let mut remaining = Some(domain.clone());
+        let mut shadowed = vec![];
+        for (index, rule) in self.workflows[name].rules.iter().enumerate() {
+            let Some(rectangle) = remaining else {
+                shadowed.push(index);
+                continue;
+            };
+
+            let (matching, rest) = rule.split(&rectangle);
+            if matching.is_none() {
+                shadowed.push(index);
             }
-
-            prod *= (rating_max - rating_min) + 1
+            remaining = rest;
         }
+        shadowed
+    }
 
-        prod
+    fn same_outcome(&self, name: &str, domain: &HyperRectangle) -> Option<&str> {
+        let rules = &self.workflows[name].rules;
+        let shadowed = self.shadowed_rules(name, domain);
+        let outcomes = rules.iter().enumerate()
+            .filter(|(index, _)| !shadowed.contains(index))
+            .map(|(_, rule)| rule.result())
+            .unique()
+            .collect::<Vec<_>>();
+        match outcomes[..] {
+            [outcome] if rules.len() > 1 => Some(outcome),
+            _ => None
+        }
     }
 
-    fn calculate_sum_accepted_ratings(input: &str) -> usize {
-        let lines = read_lines(input);
-        let parts = lines.split(|s| s.is_empty()).collect::<Vec<_>>();
-        let raw_workflows = parts[0];
-        let raw_ratings = parts[1];
+    // every cycle found by a depth first search, each starts from its first workflow visited
+    fn cycles(&self) -> Vec<Vec<String>> {
+        let mut cycles = vec![];
+        let mut visited = HashSet::new();
+        for name in self.workflows.keys().sorted() {
+            let mut path = vec![];
+            self.find_cycles(name, &mut path, &mut visited, &mut cycles);
+        }
+        cycles
+    }
 
-        let all_ratings = raw_ratings.iter().map(|r| parse_rating(r))
-            .collect::<Vec<_>>();
+    fn find_cycles<'a>(&'a self, name: &'a str, path: &mut Vec<&'a str>, visited: &mut HashSet<&'a str>, cycles: &mut Vec<Vec<String>>) {
+        if let Some(start) = path.iter().position(|&n| n == name) {
+            cycles.push(path[start..].iter().map(|n| n.to_string()).collect());
+            return;
+        }
+        if !visited.insert(name) {
+            return;
+        }
 
-        let workflows = parse_workflows(raw_workflows);
+        path.push(name);
+        for next in self.workflows[name].rules.iter().map(|rule| rule.result()).unique() {
+            if self.workflows.contains_key(next) {
+                self.find_cycles(next, path, visited, cycles);
+            }
+        }
+        path.pop();
+    }
 
-        let mut sum = 0;
+    // workflows whose rules all lead to the same outcome become a single default, the rules going to such a workflow
+    // go straight to its outcome instead, until nothing changes. The workflows left unreachable are removed.
+    pub fn simplified(&self, domain: &HyperRectangle) -> WorkflowSet {
+        let mut workflows: HashMap<String, Workflow> = self.workflows.iter()
+            .map(|(name, workflow)| (name.clone(), workflow.clone()))
+            .collect();
+
+        let mut changed = true;
+        while changed {
+            let simplified = WorkflowSet { workflows };
+            let defaults: HashMap<&str, &str> = simplified.workflows.keys()
+                .filter_map(|name| simplified.same_outcome(name, domain).or_else(|| simplified.workflows[name].default_only()).map(|outcome| (name.as_str(), outcome)))
+                .collect();
+
+            changed = false;
+            workflows = simplified.workflows.iter()
+                .map(|(name, workflow)| {
+                    let simplified_workflow = match defaults.get(name.as_str()) {
+                        Some(outcome) if workflow.rules.len() > 1 => Workflow { rules: vec![DefaultRule::new(outcome)], line: workflow.line },
+                        _ => workflow.retargeted(|target| defaults.get(target).filter(|&&outcome| outcome != name).unwrap_or(&target)),
+                    };
+                    changed |= simplified_workflow.results() != workflow.results();
+                    (name.clone(), simplified_workflow)
+                })
+                .collect();
+        }
 
-        for ratings in &all_ratings {
-            let mut current_workflow = &workflows["in"];
-            loop {
-                let result = current_workflow.execute(&ratings).unwrap();
-                if result == "A" {
-                    sum += ratings.values().sum::<usize>();
-                    break;
-                }
+        let simplified = WorkflowSet { workflows };
+        let reachable = reachable(["in"], |&name| simplified.next_workflows(name, domain), usize::MAX);
+        let workflows = simplified.workflows.iter()
+            .filter(|(name, _)| reachable.contains_key(name.as_str()))
+            .map(|(name, workflow)| (name.clone(), workflow.clone()))
+            .collect();
+        WorkflowSet { workflows }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Node {
+    // goes to `below` when the rating of `attribute` is lower than `threshold`, to `above` otherwise
+    Branch { attribute: usize, threshold: usize, below: usize, above: usize },
+    Leaf(Verdict),
+}
+
+// the workflows as a decision tree over ratings stored in the order of `attributes`,
+// the nodes shared by several workflows are compiled once
+pub struct CompiledWorkflows {
+    attributes: Vec<String>,
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+impl CompiledWorkflows {
+    // the ratings of the part in the order of the attributes, the missing ones are 0
+    pub fn ratings(&self, part: &Part) -> Vec<usize> {
+        self.attributes.iter().map(|attribute| part.get(attribute).copied().unwrap_or(0)).collect()
+    }
 
-                if result == "R" {
-                    break;
+    pub fn rate(&self, ratings: &[usize]) -> Verdict {
+        let mut node = self.root;
+        loop {
+            match self.nodes[node] {
+                Node::Branch { attribute, threshold, below, above } => {
+                    node = if ratings[attribute] < threshold { below } else { above };
                 }
-
-                current_workflow = &workflows[result];
+                Node::Leaf(verdict) => return verdict,
             }
         }
-
-        sum
     }
 
-    fn parse_rating(raw_rating: &str) -> HashMap<String, usize> {
-        let ratings_regex = Regex::new(r"(\w)=(\d+)").unwrap();
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
 
-        let ratings = ratings_regex.captures_iter(raw_rating).map(|c| {
-            let (_, [key, value]) = c.extract();
-            (key.to_string(), value.parse::<usize>().unwrap())
-        }).collect::<HashMap<_, _>>();
-        ratings
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl WorkflowSet {
+    // fails when a rule rates an attribute that is not in `attributes`, goes to an undefined workflow,
+    // or when workflows go round in circles, which `parse_unchecked` lets through
+    pub fn compile(&self, attributes: &[&str]) -> Result<CompiledWorkflows, String> {
+        let mut compiled = CompiledWorkflows {
+            attributes: attributes.iter().map(|attribute| attribute.to_string()).collect(),
+            nodes: vec![Node::Leaf(Verdict::Accepted), Node::Leaf(Verdict::Rejected)],
+            root: 0,
+        };
+        let mut compiled_rules = HashMap::new();
+        compiled.root = self.compile_rule("in", 0, &mut compiled, &mut compiled_rules)?;
+        Ok(compiled)
     }
 
-    fn parse_workflows(raw_workflows: &[String]) -> HashMap<&str, Workflow> {
-        let workflow_regex = Regex::new(r"(.+)\{(.+)}").unwrap();
-        let workflows = raw_workflows.iter().map(|raw_workflow| {
-            let captures: [&str; 2] = workflow_regex.captures(&raw_workflow).map(|c| c.extract()).unwrap().1;
-            (captures[0], Workflow::new(captures[1]))
-        }).collect::<HashMap<_, _>>();
-        workflows
+    // the node deciding for the parts reaching the rule at `index` of the workflow,
+    // rules being compiled are `None` in `compiled_rules`, meeting one again means a cycle
+    fn compile_rule<'a>(&'a self,
+                        workflow: &'a str,
+                        index: usize,
+                        compiled: &mut CompiledWorkflows,
+                        compiled_rules: &mut HashMap<(&'a str, usize), Option<usize>>) -> Result<usize, String> {
+        match workflow {
+            "A" => return Ok(0),
+            "R" => return Ok(1),
+            _ => {}
+        }
+        match compiled_rules.get(&(workflow, index)) {
+            Some(Some(node)) => return Ok(*node),
+            Some(None) => return Err(format!("workflow `{}` leads back to itself", workflow)),
+            None => {}
+        }
+
+        let rule = &self.workflows.get(workflow)
+            .ok_or_else(|| format!("unknown workflow `{}`", workflow))?
+            .rules[index];
+        compiled_rules.insert((workflow, index), None);
+        let target = self.compile_rule(rule.result(), 0, compiled, compiled_rules)?;
+        let node = match rule.condition() {
+            None => target,
+            Some((key, ordering, value)) => {
+                let attribute = compiled.attributes.iter().position(|attribute| attribute == key)
+                    .ok_or_else(|| format!("unknown attribute `{}` in `{}`", key, workflow))?;
+                let rest = self.compile_rule(workflow, index + 1, compiled, compiled_rules)?;
+                let branch = match ordering {
+                    Ordering::Less => Some(Node::Branch { attribute, threshold: value, below: target, above: rest }),
+                    // no rating is greater than the largest one
+                    _ => value.checked_add(1).map(|threshold| Node::Branch { attribute, threshold, below: rest, above: target }),
+                };
+                match branch {
+                    Some(branch) => {
+                        compiled.nodes.push(branch);
+                        compiled.nodes.len() - 1
+                    }
+                    None => rest
+                }
+            }
+        };
+        compiled_rules.insert((workflow, index), Some(node));
+        Ok(node)
+    }
+}
+
+// how long rating the same random parts takes, through `WorkflowSet::verdict` and through the compiled workflows
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineComparison {
+    pub parts: usize,
+    pub interpreted_ms: f64,
+    pub compiled_ms: f64,
+}
+
+impl EngineComparison {
+    pub fn parts_per_second(&self, ms: f64) -> f64 {
+        self.parts as f64 / (ms / 1000.0)
+    }
+}
+
+pub fn compare_engines(input: &str, parts: usize, runs: usize) -> Result<EngineComparison, ParseError> {
+    let workflows = WorkflowSet::parse(input)?;
+    let compiled = workflows.compile(&XMAS).unwrap();
+    let mut random = StdRng::seed_from_u64(19);
+    let random_parts: Vec<Part> = (0..parts)
+        .map(|_| XMAS.iter().map(|attribute| (attribute.to_string(), random.gen_range(1..=4000))).collect())
+        .collect();
+    let ratings: Vec<Vec<usize>> = random_parts.iter().map(|part| compiled.ratings(part)).collect();
+
+    let interpreted_ms = fastest(runs, || {
+        Ok(random_parts.iter().filter(|part| workflows.verdict(part) == Ok(Verdict::Accepted)).count())
+    })?;
+    let compiled_ms = fastest(runs, || {
+        Ok(ratings.iter().filter(|ratings| compiled.rate(ratings) == Verdict::Accepted).count())
+    })?;
+    Ok(EngineComparison { parts, interpreted_ms, compiled_ms })
+}
+
+// how many parts are inside all the rectangles, none when that does not fit
+pub fn total_volume(rectangles: &[HyperRectangle]) -> Option<u128> {
+    rectangles.iter().try_fold(0u128, |total, rectangle| total.checked_add(rectangle.volume()?))
+}
+
+pub fn calculate_distinct_combinations(input: &str) -> Result<u128, SolveError> {
+    let workflows = WorkflowSet::parse(input)?;
+    let accepted = workflows.accepted_rectangles(&HyperRectangle::xmas()).map_err(|reason| SolveError::unsolvable(19, reason))?;
+    total_volume(&accepted)
+        .ok_or_else(|| SolveError::unsolvable(19, "too many combinations to count"))
+}
+
+pub fn calculate_sum_accepted_ratings(input: &str) -> Result<usize, ParseError> {
+    let all_ratings = parse_parts(input)?;
+    let workflows = WorkflowSet::parse(input)?.compile(&XMAS).unwrap();
+
+    let sum = all_ratings.iter()
+        .filter(|ratings| workflows.rate(&workflows.ratings(ratings)) == Verdict::Accepted)
+        .map(|ratings| ratings.values().sum::<usize>())
+        .sum();
+
+    Ok(sum)
+}
+
+fn split_input(input: &str) -> (Vec<Line<'_>>, Vec<Line<'_>>) {
+    let mut lines = lines(19, input);
+    let raw_workflows = lines.by_ref().take_while(|l| !l.text.is_empty()).collect();
+    (raw_workflows, lines.collect())
+}
+
+fn parse_rating(raw_rating: &Line, attributes: &[&str]) -> Result<Part, ParseError> {
+    let values = raw_rating.text.strip_prefix('{')
+        .and_then(|r| r.strip_suffix('}'))
+        .ok_or_else(|| {
+            let expected = attributes.iter().map(|attribute| format!("{}=..", attribute)).join(",");
+            raw_rating.error(raw_rating.text, format!("expected `{{{}}}`, found `{}`", expected, raw_rating.text))
+        })?;
+
+    let mut ratings = HashMap::new();
+    for raw_value in values.split(',') {
+        let (key, value) = raw_rating.split_once(raw_value, "=")?;
+        ratings.insert(parse_key(raw_rating, key, attributes)?.to_string(), raw_rating.parse::<usize>(value)?);
     }
 
-    trait Rule {
-        fn execute(&self, ratings: &HashMap<String, usize>) -> Option<&str>;
-        fn opposite(&self) -> Box<dyn Rule>;
-        fn result(&self) -> &str;
-        fn key(&self) -> &str;
+    if let Some(missing) = attributes.iter().find(|attribute| !ratings.contains_key(**attribute)) {
+        return Err(raw_rating.error(raw_rating.text, format!("missing rating `{}`", missing)));
+    }
 
-        fn update(&self, rating_max: &mut usize, rating_min: &mut usize);
+    Ok(ratings)
+}
 
-        fn copy(&self) -> Box<dyn Rule>;
+fn parse_key<'a>(line: &Line<'a>, key: &'a str, attributes: &[&str]) -> Result<&'a str, ParseError> {
+    if attributes.contains(&key) {
+        Ok(key)
+    } else {
+        Err(line.error(key, format!("unknown rating `{}`", key)))
+    }
+}
+
+fn parse_workflows<'a>(raw_workflows: &[Line<'a>], attributes: &[&str], check_targets: bool) -> Result<HashMap<&'a str, Workflow>, ParseError> {
+    let workflow_regex = Regex::new(r"^(\w+)\{(.+)}$").unwrap();
+    let raw_workflows = raw_workflows.iter().map(|raw_workflow| {
+        let captures: [&str; 2] = workflow_regex.captures(raw_workflow.text)
+            .map(|c| c.extract().1)
+            .ok_or_else(|| raw_workflow.error(raw_workflow.text, format!("expected `<name>{{<rules>}}`, found `{}`", raw_workflow.text)))?;
+        Ok((raw_workflow, captures[0], captures[1]))
+    }).collect::<Result<Vec<_>, ParseError>>()?;
+
+    let names = raw_workflows.iter().map(|(_, name, _)| *name).collect::<Vec<_>>();
+    let mut workflows = HashMap::new();
+    for (line, name, raw_rules) in raw_workflows {
+        for target in raw_rules.split(',').map(|r| r.rsplit(':').next().unwrap()) {
+            if check_targets && target != "A" && target != "R" && !names.contains(&target) {
+                return Err(line.error(target, format!("unknown workflow `{}`", target)));
+            }
+        }
+        workflows.insert(name, Workflow::new(line, raw_rules, attributes)?);
     }
 
-    struct Workflow {
-        rules: Vec<Box<dyn Rule>>,
+    if check_targets && !workflows.contains_key("in") {
+        return Err(ParseError::end_of_input(19, workflows.len() + 1, "the `in` workflow"));
     }
 
-    impl Workflow {
-        fn new(raw_rules: &str) -> Self {
-            let mut rules: Vec<Box<dyn Rule>> = vec![];
+    Ok(workflows)
+}
 
-            for rule in raw_rules.split(",") {
-                if rule.contains("<") {
-                    rules.push(LowerRule::new(rule))
-                } else if rule.contains(">") {
-                    rules.push(GreaterRule::new(rule))
-                } else {
-                    rules.push(DefaultRule::new(rule))
-                }
-            }
+pub trait Rule {
+    // the workflow to go to, `A` or `R`, when the rule applies to the part
+    fn execute(&self, ratings: &Part) -> Option<&str>;
+    fn result(&self) -> &str;
 
-            Workflow { rules }
-        }
+    // the parts of the rectangle the rule applies to, and the others
+    fn split(&self, rectangle: &HyperRectangle) -> (Option<HyperRectangle>, Option<HyperRectangle>);
 
-        fn execute(&self, ratings: &HashMap<String, usize>) -> Option<&str> {
-            for rule in &self.rules {
-                if let Some(result) = rule.execute(ratings) {
-                    return Some(result);
-                }
+    // the same rule going somewhere else
+    fn retarget(&self, result: &str) -> Box<dyn Rule>;
+
+    // the rating compared, how and the value compared with: `Less` for `x<10`. Default rules have none.
+    fn condition(&self) -> Option<(&str, Ordering, usize)>;
+}
+
+pub struct Workflow {
+    rules: Vec<Box<dyn Rule>>,
+    line: usize,
+}
+
+impl Workflow {
+    fn new<'a>(line: &Line<'a>, raw_rules: &'a str, attributes: &[&str]) -> Result<Self, ParseError> {
+        let mut rules: Vec<Box<dyn Rule>> = vec![];
+
+        for rule in raw_rules.split(',') {
+            if rule.contains('<') {
+                rules.push(LowerRule::new(line, rule, attributes)?)
+            } else if rule.contains('>') {
+                rules.push(GreaterRule::new(line, rule, attributes)?)
+            } else {
+                rules.push(DefaultRule::new(rule))
             }
+        }
 
-            panic!("No rule matches!")
+        let last_rule = raw_rules.rsplit(',').next().unwrap();
+        if last_rule.contains(':') {
+            return Err(line.error(last_rule, format!("expected a fallback workflow, found `{}`", last_rule)));
         }
+
+        Ok(Workflow { rules, line: line.number })
     }
 
-    #[derive(Debug)]
-    struct LowerRule {
-        key: String,
-        value: usize,
-        result: String,
+    pub fn rules(&self) -> &[Box<dyn Rule>] {
+        &self.rules
     }
 
-    impl LowerRule {
-        fn new(raw_rule: &str) -> Box<Self> {
-            let rule_parts = raw_rule.split(":").collect::<Vec<_>>();
-            let raw_activation = rule_parts[0];
-            let result = rule_parts[1];
-            let activation_parts = raw_activation.split("<").collect::<Vec<_>>();
-            let key = activation_parts[0];
-            let value = activation_parts[1].parse::<usize>().unwrap();
-            Box::new(LowerRule { key: key.to_string(), result: result.to_string(), value })
-        }
+    fn results(&self) -> Vec<&str> {
+        self.rules.iter().map(|rule| rule.result()).collect()
     }
 
-    impl Rule for LowerRule {
-        fn execute(&self, ratings: &HashMap<String, usize>) -> Option<&str> {
-            if *(ratings.get(&self.key).unwrap()) < self.value {
-                Some(&self.result)
-            } else {
-                None
-            }
-        }
+    fn retargeted<'a>(&'a self, target: impl Fn(&'a str) -> &'a str) -> Workflow {
+        Workflow { rules: self.rules.iter().map(|rule| rule.retarget(target(rule.result()))).collect(), line: self.line }
+    }
 
-        fn opposite(&self) -> Box<dyn Rule> {
-            Box::new(GreaterEqualRule { value: self.value, key: self.key.clone(), result: self.result.clone() })
+    // the outcome of a workflow made of a single default rule
+    fn default_only(&self) -> Option<&str> {
+        match &self.rules[..] {
+            [rule] => Some(rule.result()),
+            _ => None
         }
+    }
 
-        fn result(&self) -> &str {
-            &self.result
-        }
+    // the last rule always applies, the parser makes sure of it
+    pub fn execute(&self, ratings: &Part) -> &str {
+        self.rules.iter()
+            .find_map(|rule| rule.execute(ratings))
+            .unwrap()
+    }
+}
 
-        fn key(&self) -> &str {
-            &self.key
-        }
+impl Clone for Workflow {
+    fn clone(&self) -> Self {
+        self.retargeted(|target| target)
+    }
+}
+
+#[derive(Debug)]
+struct LowerRule {
+    key: String,
+    value: usize,
+    result: String,
+}
+
+impl LowerRule {
+    fn new<'a>(line: &Line<'a>, raw_rule: &'a str, attributes: &[&str]) -> Result<Box<Self>, ParseError> {
+        let (raw_activation, result) = line.split_once(raw_rule, ":")?;
+        let (key, value) = line.split_once(raw_activation, "<")?;
+        let key = parse_key(line, key, attributes)?;
+        let value = line.parse::<usize>(value)?;
+        Ok(Box::new(LowerRule { key: key.to_string(), result: result.to_string(), value }))
+    }
+}
 
-        fn update(&self, rating_max: &mut usize, _rating_min: &mut usize) {
-            *rating_max = min(*rating_max, self.value - 1)
+impl Rule for LowerRule {
+    fn execute(&self, ratings: &Part) -> Option<&str> {
+        if *(ratings.get(&self.key).unwrap()) < self.value {
+            Some(&self.result)
+        } else {
+            None
         }
+    }
 
-        fn copy(&self) -> Box<dyn Rule> {
-            Box::new(Self { value: self.value, result: self.result.clone(), key: self.key.clone() })
+    fn result(&self) -> &str {
+        &self.result
+    }
+
+    fn split(&self, rectangle: &HyperRectangle) -> (Option<HyperRectangle>, Option<HyperRectangle>) {
+        match self.value.checked_sub(1) {
+            Some(highest) => rectangle.split(&self.key, 0..=highest),
+            None => (None, Some(rectangle.clone()))
         }
     }
 
-    #[derive(Debug)]
-    struct LowerEqualRule {
-        key: String,
-        value: usize,
-        result: String,
+    fn retarget(&self, result: &str) -> Box<dyn Rule> {
+        Box::new(LowerRule { key: self.key.clone(), value: self.value, result: result.to_string() })
     }
 
-    impl Rule for LowerEqualRule {
-        fn execute(&self, ratings: &HashMap<String, usize>) -> Option<&str> {
-            if *(ratings.get(&self.key).unwrap()) <= self.value {
-                Some(&self.result)
-            } else {
-                None
-            }
-        }
+    fn condition(&self) -> Option<(&str, Ordering, usize)> {
+        Some((&self.key, Ordering::Less, self.value))
+    }
+}
+
+#[derive(Debug)]
+struct GreaterRule {
+    key: String,
+    value: usize,
+    result: String,
+}
+
+impl GreaterRule {
+    fn new<'a>(line: &Line<'a>, raw_rule: &'a str, attributes: &[&str]) -> Result<Box<Self>, ParseError> {
+        let (raw_activation, result) = line.split_once(raw_rule, ":")?;
+        let (key, value) = line.split_once(raw_activation, ">")?;
+        let key = parse_key(line, key, attributes)?;
+        let value = line.parse::<usize>(value)?;
+        Ok(Box::new(GreaterRule { key: key.to_string(), result: result.to_string(), value }))
+    }
+}
 
-        fn opposite(&self) -> Box<dyn Rule> {
-            Box::new(GreaterRule { value: self.value, key: self.key.clone(), result: self.result.clone() })
+impl Rule for GreaterRule {
+    fn execute(&self, ratings: &Part) -> Option<&str> {
+        if *(ratings.get(&self.key).unwrap()) > self.value {
+            Some(&self.result)
+        } else {
+            None
         }
+    }
 
-        fn result(&self) -> &str {
-            &self.result
-        }
+    fn result(&self) -> &str {
+        &self.result
+    }
 
-        fn key(&self) -> &str {
-            &self.key
+    fn split(&self, rectangle: &HyperRectangle) -> (Option<HyperRectangle>, Option<HyperRectangle>) {
+        match self.value.checked_add(1) {
+            Some(lowest) => rectangle.split(&self.key, lowest..=usize::MAX),
+            None => (None, Some(rectangle.clone()))
         }
+    }
 
-        fn update(&self, rating_max: &mut usize, _rating_min: &mut usize) {
-            *rating_max = min(*rating_max, self.value)
-        }
+    fn retarget(&self, result: &str) -> Box<dyn Rule> {
+        Box::new(GreaterRule { key: self.key.clone(), value: self.value, result: result.to_string() })
+    }
 
-        fn copy(&self) -> Box<dyn Rule> {
-            Box::new(Self { value: self.value, result: self.result.clone(), key: self.key.clone() })
-        }
+    fn condition(&self) -> Option<(&str, Ordering, usize)> {
+        Some((&self.key, Ordering::Greater, self.value))
     }
+}
+
+#[derive(Debug)]
+struct DefaultRule {
+    result: String,
+}
 
-    #[derive(Debug)]
-    struct GreaterRule {
-        key: String,
-        value: usize,
-        result: String,
+impl DefaultRule {
+    fn new(result: &str) -> Box<Self> {
+        Box::new(DefaultRule { result: result.to_string() })
     }
+}
 
-    impl GreaterRule {
-        fn new(raw_rule: &str) -> Box<Self> {
-            let rule_parts = raw_rule.split(":").collect::<Vec<_>>();
-            let raw_activation = rule_parts[0];
-            let result = rule_parts[1];
-            let activation_parts = raw_activation.split(">").collect::<Vec<_>>();
-            let key = activation_parts[0];
-            let value = activation_parts[1].parse::<usize>().unwrap();
-            Box::new(GreaterRule { key: key.to_string(), result: result.to_string(), value })
-        }
+impl Rule for DefaultRule {
+    fn execute(&self, _ratings: &Part) -> Option<&str> {
+        Some(&self.result)
     }
 
-    impl Rule for GreaterRule {
-        fn execute(&self, ratings: &HashMap<String, usize>) -> Option<&str> {
-            if *(ratings.get(&self.key).unwrap()) > self.value {
-                Some(&self.result)
-            } else {
-                None
-            }
-        }
+    fn result(&self) -> &str {
+        &self.result
+    }
 
-        fn opposite(&self) -> Box<dyn Rule> {
-            Box::new(LowerEqualRule { value: self.value, key: self.key.clone(), result: self.result.clone() })
-        }
+    fn split(&self, rectangle: &HyperRectangle) -> (Option<HyperRectangle>, Option<HyperRectangle>) {
+        (Some(rectangle.clone()), None)
+    }
 
-        fn result(&self) -> &str {
-            &self.result
-        }
+    fn retarget(&self, result: &str) -> Box<dyn Rule> {
+        DefaultRule::new(result)
+    }
 
-        fn key(&self) -> &str {
-            &self.key
-        }
+    fn condition(&self) -> Option<(&str, Ordering, usize)> {
+        None
+    }
+}
 
-        fn update(&self, _rating_max: &mut usize, rating_min: &mut usize) {
-            *rating_min = max(*rating_min, self.value + 1)
-        }
+pub struct Day19;
 
+impl Solution for Day19 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        let (_, raw_ratings) = split_input(input);
+        Some(WorkflowSet::parse(input).and_then(|_| raw_ratings.iter().try_for_each(|r| parse_rating(r, &XMAS).map(|_| ()))))
+    }
 
-        fn copy(&self) -> Box<dyn Rule> {
-            Box::new(Self { value: self.value, result: self.result.clone(), key: self.key.clone() })
-        }
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_sum_accepted_ratings(input)?.into())
     }
 
-    #[derive(Debug)]
-    struct GreaterEqualRule {
-        key: String,
-        value: usize,
-        result: String,
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        let combinations = calculate_distinct_combinations(input)?;
+        let combinations = i128::try_from(combinations).map_err(|_| SolveError::unsolvable(19, "too many combinations to count"))?;
+        Ok(combinations.into())
     }
+}
 
-    impl Rule for GreaterEqualRule {
-        fn execute(&self, ratings: &HashMap<String, usize>) -> Option<&str> {
-            if *(ratings.get(&self.key).unwrap()) >= self.value {
-                Some(&self.result)
-            } else {
-                None
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
 
-        fn opposite(&self) -> Box<dyn Rule> {
-            Box::new(LowerRule { value: self.value, key: self.key.clone(), result: self.result.clone() })
-        }
+    use crate::day19::*;
+    use crate::input_reader::read_input_file;
 
-        fn result(&self) -> &str {
-            &self.result
-        }
+    #[test]
+    fn it_solves_first_part() {
+        let input = &read_input_file("input_day19.txt");
 
-        fn key(&self) -> &str {
-            &self.key
-        }
+        assert_eq!(Ok(476889), calculate_sum_accepted_ratings(input));
+    }
 
-        fn update(&self, _rating_max: &mut usize, rating_min: &mut usize) {
-            *rating_min = max(*rating_min, self.value)
-        }
+    #[test]
+    fn it_solves_second_part() {
+        let input = &read_input_file("input_day19.txt");
 
-        fn copy(&self) -> Box<dyn Rule> {
-            Box::new(Self { value: self.value, result: self.result.clone(), key: self.key.clone() })
-        }
+        assert_eq!(Ok(132380153677887), calculate_distinct_combinations(input));
     }
 
-    #[derive(Debug)]
-    struct DefaultRule {
-        result: String,
+    #[test]
+    fn it_calculates_sum_accepted_ratings() {
+        let input = indoc! {"
+        px{a<2006:qkq,m>2090:A,rfg}
+        pv{a>1716:R,A}
+        lnx{m>1548:A,A}
+        rfg{s<537:gd,x>2440:R,A}
+        qs{s>3448:A,lnx}
+        qkq{x<1416:A,crn}
+        crn{x>2662:A,R}
+        in{s<1351:px,qqz}
+        qqz{s>2770:qs,m<1801:hdj,R}
+        gd{a>3333:R,R}
+        hdj{m>838:A,pv}
+
+        {x=787,m=2655,a=1222,s=2876}
+        {x=1679,m=44,a=2067,s=496}
+        {x=2036,m=264,a=79,s=2244}
+        {x=2461,m=1339,a=466,s=291}
+        {x=2127,m=1623,a=2188,s=1013}"};
+
+        assert_eq!(Ok(19114), calculate_sum_accepted_ratings(input));
     }
 
-    impl DefaultRule {
-        fn new(result: &str) -> Box<Self> {
-            Box::new(DefaultRule { result: result.to_string() })
-        }
+    #[test]
+    fn it_calculates_distinct_combinations() {
+        let input = indoc! {"
+        px{a<2006:qkq,m>2090:A,rfg}
+        pv{a>1716:R,A}
+        lnx{m>1548:A,A}
+        rfg{s<537:gd,x>2440:R,A}
+        qs{s>3448:A,lnx}
+        qkq{x<1416:A,crn}
+        crn{x>2662:A,R}
+        in{s<1351:px,qqz}
+        qqz{s>2770:qs,m<1801:hdj,R}
+        gd{a>3333:R,R}
+        hdj{m>838:A,pv}
+
+        {x=787,m=2655,a=1222,s=2876}
+        {x=1679,m=44,a=2067,s=496}
+        {x=2036,m=264,a=79,s=2244}
+        {x=2461,m=1339,a=466,s=291}
+        {x=2127,m=1623,a=2188,s=1013}"};
+
+        assert_eq!(Ok(167409079868000), calculate_distinct_combinations(input));
+        assert_eq!(None, find_overlap(&WorkflowSet::parse(input).unwrap().accepted_rectangles(&HyperRectangle::xmas()).unwrap()));
     }
 
-    impl Rule for DefaultRule {
-        fn execute(&self, _ratings: &HashMap<String, usize>) -> Option<&str> {
-            Some(&self.result)
-        }
+    #[test]
+    fn it_evaluates_parts() {
+        let input = indoc! {"
+        px{a<2006:qkq,m>2090:A,rfg}
+        pv{a>1716:R,A}
+        lnx{m>1548:A,A}
+        rfg{s<537:gd,x>2440:R,A}
+        qs{s>3448:A,lnx}
+        qkq{x<1416:A,crn}
+        crn{x>2662:A,R}
+        in{s<1351:px,qqz}
+        qqz{s>2770:qs,m<1801:hdj,R}
+        gd{a>3333:R,R}
+        hdj{m>838:A,pv}
 
-        fn opposite(&self) -> Box<dyn Rule> {
-            panic!("no possible!")
-        }
+        {x=787,m=2655,a=1222,s=2876}
+        {x=1679,m=44,a=2067,s=496}
+        {x=2036,m=264,a=79,s=2244}"};
+
+        let workflows = WorkflowSet::parse(input).unwrap();
+        let evaluations: Vec<_> = parse_parts(input).unwrap().iter()
+            .map(|part| workflows.evaluate(part).unwrap())
+            .map(|evaluation| (evaluation.verdict, evaluation.chain.join(" -> ")))
+            .collect();
+        assert_eq!(vec![
+            (Verdict::Accepted, "in -> qqz -> qs -> lnx".to_string()),
+            (Verdict::Rejected, "in -> px -> rfg -> gd".to_string()),
+            (Verdict::Accepted, "in -> qqz -> hdj -> pv".to_string()),
+        ], evaluations);
+        assert_eq!("A", workflows.workflow("lnx").unwrap().execute(&parse_parts(input).unwrap()[0]));
+        assert_eq!(2, workflows.workflow("pv").unwrap().rules().len());
+    }
 
-        fn result(&self) -> &str {
-            &self.result
-        }
+    #[test]
+    fn it_finds_accepted_rectangles() {
+        let input = indoc! {"
+        in{x<10:low,m>5:A,R}
+        low{x>3:A,R}"};
+        let workflows = WorkflowSet::parse(input).unwrap();
+
+        let domain = HyperRectangle::new(&[("x", 1..=20), ("m", 1..=10)]);
+        let accepted = workflows.accepted_rectangles(&domain).unwrap();
+        assert_eq!(vec![
+            HyperRectangle::new(&[("x", 10..=20), ("m", 6..=10)]),
+            HyperRectangle::new(&[("x", 4..=9), ("m", 1..=10)]),
+        ], accepted);
+        assert_eq!(vec![Some(55), Some(60)], accepted.iter().map(HyperRectangle::volume).collect::<Vec<_>>());
+        assert_eq!(Some(115), total_volume(&accepted));
+        assert_eq!(None, find_overlap(&accepted));
+
+        // `m` is not in the domain, its rule never applies
+        let accepted = workflows.accepted_rectangles(&HyperRectangle::new(&[("x", 0..=9)])).unwrap();
+        assert_eq!(vec![HyperRectangle::new(&[("x", 4..=9)])], accepted);
+
+        let overlapping = [HyperRectangle::new(&[("x", 1..=5)]), HyperRectangle::new(&[("x", 7..=8)]), HyperRectangle::new(&[("x", 5..=6)])];
+        assert_eq!(Some((0, 2)), find_overlap(&overlapping));
+    }
 
-        fn key(&self) -> &str {
-            panic!("no key!")
-        }
+    #[test]
+    fn it_splits_on_extreme_thresholds() {
+        assert_eq!(Ok(0), calculate_distinct_combinations("in{x>0:R,A}"));
+        assert_eq!(Ok(256000000000000), calculate_distinct_combinations("in{x<1:R,A}"));
+        assert_eq!(Ok(256000000000000), calculate_distinct_combinations("in{x<0:R,A}"));
+        assert_eq!(Ok(256000000000000), calculate_distinct_combinations("in{x>18446744073709551615:R,A}"));
+        assert_eq!(Ok(0), calculate_distinct_combinations("in{x>4000:A,x<1:A,R}"));
+    }
 
-        fn update(&self, _rating_max: &mut usize, _rating_min: &mut usize) {
-            panic!("not allowed")
-        }
+    #[test]
+    fn it_measures_huge_volumes() {
+        let everything = HyperRectangle::new(&[("x", 0..=usize::MAX)]);
+        assert_eq!(Some(usize::MAX as u128 + 1), everything.volume());
+        assert_eq!(Some(0), HyperRectangle::new(&[("x", 0..=usize::MAX), ("m", RangeInclusive::new(5, 4))]).volume());
+        assert_eq!(None, HyperRectangle::new(&[("x", 0..=usize::MAX), ("m", 0..=usize::MAX), ("a", 0..=usize::MAX)]).volume());
+        assert_eq!(None, total_volume(&[HyperRectangle::new(&[("x", 0..=usize::MAX), ("m", 0..=usize::MAX)]), everything]));
+    }
 
-        fn copy(&self) -> Box<dyn Rule> {
-            Box::new(Self { result: self.result.clone() })
+    #[test]
+    fn it_validates_workflows() {
+        let input = indoc! {"
+        in{x>10:a,x>20:b,R}
+        a{m<5:c,a<3:zz,A}
+        c{s>3:a,a}
+        b{x<5:R,R}"};
+
+        let workflows = WorkflowSet::parse_unchecked(input).unwrap();
+        assert_eq!(vec![
+            Issue::UndefinedTarget { workflow: "a".to_string(), target: "zz".to_string() },
+            Issue::Unreachable("b".to_string()),
+            Issue::ShadowedRule { workflow: "in".to_string(), rule: 1 },
+            Issue::Cycle(vec!["a".to_string(), "c".to_string()]),
+            Issue::SameOutcome { workflow: "b".to_string(), outcome: "R".to_string() },
+            Issue::SameOutcome { workflow: "c".to_string(), outcome: "a".to_string() },
+        ], workflows.validate(&HyperRectangle::xmas()));
+
+        // with `x` up to 10, `a` and the workflows after it are never reached
+        let domain = HyperRectangle::new(&[("x", 1..=10), ("m", 1..=4000), ("a", 1..=4000), ("s", 1..=4000)]);
+        assert_eq!(vec![
+            Issue::UndefinedTarget { workflow: "a".to_string(), target: "zz".to_string() },
+            Issue::Unreachable("a".to_string()),
+            Issue::Unreachable("b".to_string()),
+            Issue::Unreachable("c".to_string()),
+            Issue::ShadowedRule { workflow: "in".to_string(), rule: 0 },
+            Issue::ShadowedRule { workflow: "in".to_string(), rule: 1 },
+            Issue::Cycle(vec!["a".to_string(), "c".to_string()]),
+            Issue::SameOutcome { workflow: "b".to_string(), outcome: "R".to_string() },
+            Issue::SameOutcome { workflow: "c".to_string(), outcome: "a".to_string() },
+            Issue::SameOutcome { workflow: "in".to_string(), outcome: "R".to_string() },
+        ], workflows.validate(&domain));
+        assert_eq!(vec!["R"], workflows.simplified(&domain).workflow("in").unwrap().results());
+
+        assert_eq!(Err(ParseError::new(19, 2, 13, "unknown workflow `zz`")), WorkflowSet::parse(input).map(|_| ()));
+        let without_entry = WorkflowSet::parse_unchecked("a{x>10:R,A}").unwrap();
+        assert_eq!(vec![Issue::MissingEntry], without_entry.validate(&HyperRectangle::xmas()));
+        let looping = "in{x>10:a,R}\na{m<5:in,A}\n\n{x=787,m=2,a=1222,s=2876}";
+        assert_eq!(Err(ParseError::new(19, 2, 1, "workflows `a -> in -> a` form a cycle")), calculate_sum_accepted_ratings(looping));
+    }
+
+    #[test]
+    fn it_simplifies_workflows() {
+        let input = indoc! {"
+        px{a<2006:qkq,m>2090:A,rfg}
+        pv{a>1716:R,A}
+        lnx{m>1548:A,A}
+        rfg{s<537:gd,x>2440:R,A}
+        qs{s>3448:A,lnx}
+        qkq{x<1416:A,crn}
+        crn{x>2662:A,R}
+        in{s<1351:px,qqz}
+        qqz{s>2770:qs,m<1801:hdj,R}
+        gd{a>3333:R,R}
+        hdj{m>838:A,pv}
+
+        {x=787,m=2655,a=1222,s=2876}
+        {x=1679,m=44,a=2067,s=496}
+        {x=2036,m=264,a=79,s=2244}
+        {x=2461,m=1339,a=466,s=291}
+        {x=2127,m=1623,a=2188,s=1013}"};
+
+        let workflows = WorkflowSet::parse(input).unwrap();
+        assert_eq!(vec![
+            Issue::SameOutcome { workflow: "gd".to_string(), outcome: "R".to_string() },
+            Issue::SameOutcome { workflow: "lnx".to_string(), outcome: "A".to_string() },
+        ], workflows.validate(&HyperRectangle::xmas()));
+
+        let simplified = workflows.simplified(&HyperRectangle::xmas());
+        assert_eq!(Vec::<Issue>::new(), simplified.validate(&HyperRectangle::xmas()));
+        assert!(simplified.workflow("lnx").is_none());
+        assert!(simplified.workflow("qs").is_none());
+        assert_eq!(vec!["A", "hdj", "R"], simplified.workflow("qqz").unwrap().results());
+        assert_eq!(vec!["R", "R", "A"], simplified.workflow("rfg").unwrap().results());
+
+        let verdicts = |workflows: &WorkflowSet| parse_parts(input).unwrap().iter().map(|part| workflows.evaluate(part).unwrap().verdict).collect::<Vec<_>>();
+        assert_eq!(verdicts(&workflows), verdicts(&simplified));
+        let volume = |workflows: &WorkflowSet| total_volume(&workflows.accepted_rectangles(&HyperRectangle::xmas()).unwrap());
+        assert_eq!(volume(&workflows), volume(&simplified));
+    }
+
+    #[test]
+    fn it_compiles_workflows() {
+        let input = &read_input_file("input_day19.txt");
+        let workflows = WorkflowSet::parse(input).unwrap();
+        let compiled = workflows.compile(&XMAS).unwrap();
+
+        let mut random = StdRng::seed_from_u64(2023);
+        for _ in 0..10000 {
+            let part: Part = XMAS.iter().map(|attribute| (attribute.to_string(), random.gen_range(1..=4000))).collect();
+            assert_eq!(workflows.evaluate(&part).unwrap().verdict, compiled.rate(&compiled.ratings(&part)));
+            assert_eq!(Ok(workflows.evaluate(&part).unwrap().verdict), workflows.verdict(&part));
         }
+
+        let simple = WorkflowSet::parse("in{x<10:a,m>5:A,R}\na{x>3:A,R}").unwrap();
+        let compiled = simple.compile(&["m", "x"]).unwrap();
+        assert_eq!(5, compiled.len());
+        assert_eq!(vec![Verdict::Accepted, Verdict::Rejected, Verdict::Accepted, Verdict::Rejected],
+                   [[0, 4], [0, 3], [6, 10], [5, 10]].iter().map(|ratings| compiled.rate(ratings)).collect::<Vec<_>>());
+        assert_eq!(Err("unknown attribute `m` in `in`".to_string()), simple.compile(&["x"]).map(|c| c.len()));
+
+        let undefined = WorkflowSet::parse_unchecked("in{x<10:a,m>5:A,R}").unwrap();
+        assert_eq!(Err("unknown workflow `a`".to_string()), undefined.compile(&XMAS).map(|c| c.len()));
+        let looping = WorkflowSet::parse_unchecked("in{x>10:a,R}\na{m<5:in,A}").unwrap();
+        assert_eq!(Err("workflow `in` leads back to itself".to_string()), looping.compile(&XMAS).map(|c| c.len()));
+    }
+
+    #[test]
+    fn it_fails_gracefully_on_unchecked_workflows() {
+        let part: Part = XMAS.iter().map(|attribute| (attribute.to_string(), 1)).collect();
+        let without_entry = WorkflowSet::parse_unchecked("a{x>10:R,A}").unwrap();
+        assert_eq!(Err("unknown workflow `in`".to_string()), without_entry.verdict(&part));
+        assert_eq!(Err("unknown workflow `in`".to_string()), without_entry.evaluate(&part));
+        assert_eq!(Err("unknown workflow `in`".to_string()), without_entry.accepted_rectangles(&HyperRectangle::xmas()));
+
+        let undefined = WorkflowSet::parse_unchecked("in{x<10:a,m>5:A,R}").unwrap();
+        assert_eq!(Err("unknown workflow `a`".to_string()), undefined.verdict(&part));
+        assert_eq!(Err("unknown workflow `a`".to_string()), undefined.accepted_rectangles(&HyperRectangle::xmas()));
+
+        let looping = WorkflowSet::parse_unchecked("in{x<10:a,R}\na{m<5:b,A}\nb{in}").unwrap();
+        assert_eq!(Err("workflow `a` leads back to itself".to_string()), looping.verdict(&part));
+        assert_eq!(Err("workflow `a` leads back to itself".to_string()), looping.evaluate(&part));
+        assert!(looping.accepted_rectangles(&HyperRectangle::xmas()).is_err());
+
+        let never = WorkflowSet::parse("in{x>18446744073709551615:R,A}").unwrap().compile(&XMAS).unwrap();
+        assert_eq!(Verdict::Accepted, never.rate(&[usize::MAX, 0, 0, 0]));
+    }
+
+    #[test]
+    fn it_compares_the_engines() {
+        let input = &read_input_file("input_day19.txt");
+
+        let comparison = compare_engines(input, 1000, 2).unwrap();
+        assert_eq!(1000, comparison.parts);
+        assert!(comparison.parts_per_second(comparison.compiled_ms) > 0.0);
+        assert!(comparison.parts_per_second(comparison.interpreted_ms) > 0.0);
+    }
+
+    #[test]
+    fn it_parses_other_attributes() {
+        let input = indoc! {"
+        in{cost>10:R,weight<3:A,heavy}
+        heavy{cost<5:A,R}
+
+        {cost=3,weight=7}
+        {cost=8,weight=7}"};
+        let attributes = ["cost", "weight"];
+
+        let workflows = WorkflowSet::parse_with(input, &attributes).unwrap();
+        let parts = parse_parts_with(input, &attributes).unwrap();
+        let verdicts = parts.iter().map(|part| workflows.evaluate(part).unwrap().verdict).collect::<Vec<_>>();
+        assert_eq!(vec![Verdict::Accepted, Verdict::Rejected], verdicts);
+
+        let compiled = workflows.compile(&attributes).unwrap();
+        assert_eq!(verdicts, parts.iter().map(|part| compiled.rate(&compiled.ratings(part))).collect::<Vec<_>>());
+
+        let domain = HyperRectangle::new(&[("cost", 1..=20), ("weight", 1..=10)]);
+        assert_eq!(Some(20 + 8 * 4), total_volume(&workflows.accepted_rectangles(&domain).unwrap()));
+
+        assert_eq!(Err(ParseError::new(19, 1, 4, "unknown rating `cost`")), WorkflowSet::parse(input).map(|_| ()));
+        assert_eq!(
+            Err(ParseError::new(19, 3, 1, "expected `{cost=..,weight=..}`, found `cost=3`")),
+            parse_parts_with("in{A}\n\ncost=3", &attributes)
+        );
+    }
+
+    #[test]
+    fn it_reports_malformed_workflows() {
+        assert_eq!(
+            Err(ParseError::new(19, 1, 11, "unknown workflow `qkq`")),
+            calculate_sum_accepted_ratings("in{a<2006:qkq,A}\n\n{x=787,m=2655,a=1222,s=2876}")
+        );
+        assert_eq!(
+            Err(ParseError::new(19, 1, 4, "unknown rating `z`")),
+            calculate_sum_accepted_ratings("in{z<2006:R,A}\n\n{x=787,m=2655,a=1222,s=2876}")
+        );
+        assert_eq!(
+            Err(ParseError::new(19, 3, 1, "missing rating `s`")),
+            calculate_sum_accepted_ratings("in{a<2006:R,A}\n\n{x=787,m=2655,a=1222}")
+        );
+        assert_eq!(
+            Err(SolveError::Parse(ParseError::new(19, 1, 13, "expected a fallback workflow, found `m>10:A`"))),
+            calculate_distinct_combinations("in{a<2006:R,m>10:A}")
+        );
     }
-}
\ No newline at end of file
+}
@@ -1,78 +1,85 @@
-use regex::{Error, Regex};
-use crate::input_reader::read_lines;
+use regex::Regex;
+use crate::parse_error::{Line, lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Debug, Clone, PartialEq)]
-struct Game {
-    id: usize,
-    extractions: Vec<Extraction>,
+pub struct Game {
+    pub id: usize,
+    pub extractions: Vec<Extraction>,
 }
 
 impl Game {
-    fn parse_games(raw_games: Vec<String>) -> Vec<Game> {
-        raw_games.iter().map(|raw_game| Game::parse_game(raw_game)).collect()
+    pub fn parse_games(raw_games: &str) -> Result<Vec<Game>, ParseError> {
+        lines(2, raw_games).map(|raw_game| Game::parse_game(&raw_game)).collect()
     }
-    fn parse_game(raw_game: &str) -> Game {
-        let parts: Vec<&str> = raw_game.split(": ").collect();
-        let game_with_id = *parts.first().unwrap();
-        let id = parse_game_id(game_with_id);
-        let raw_extractions = *parts.last().unwrap();
-        let extractions = Extraction::parse_extractions(raw_extractions);
-        Game { id, extractions }
+
+    pub fn parse_game(raw_game: &Line) -> Result<Game, ParseError> {
+        let (game_with_id, raw_extractions) = raw_game.split_once(raw_game.text, ": ")?;
+        let id = parse_game_id(raw_game, game_with_id)?;
+        let extractions = Extraction::parse_extractions(raw_game, raw_extractions)?;
+        Ok(Game { id, extractions })
     }
 
-    fn is_possible(&self) -> bool {
+    pub fn is_possible(&self) -> bool {
         self.extractions.iter().all(|e| e.is_possible())
     }
 
-    fn max_red(&self) -> usize {
+    pub fn max_red(&self) -> usize {
         self.extractions.iter()
             .map(|e| e.red.unwrap_or(0))
             .max()
             .unwrap_or(0)
     }
 
-    fn max_blue(&self) -> usize {
+    pub fn max_blue(&self) -> usize {
         self.extractions.iter()
             .map(|e| e.blue.unwrap_or(0))
             .max()
             .unwrap_or(0)
     }
 
-    fn max_green(&self) -> usize {
+    pub fn max_green(&self) -> usize {
         self.extractions.iter()
             .map(|e| e.green.unwrap_or(0))
             .max()
             .unwrap_or(0)
     }
 
-    fn power(&self) -> usize {
+    pub fn power(&self) -> usize {
         self.max_green() * self.max_red() * self.max_blue()
     }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-struct Extraction {
-    red: Option<usize>,
-    blue: Option<usize>,
-    green: Option<usize>,
+pub struct Extraction {
+    pub red: Option<usize>,
+    pub blue: Option<usize>,
+    pub green: Option<usize>,
 }
 
 impl Extraction {
-    fn parse_extraction(raw_extraction: &str) -> Self {
-        let blue = extract_color(Regex::new(r"(\d+) blue"), raw_extraction);
-        let green = extract_color(Regex::new(r"(\d+) green"), raw_extraction);
-        let red = extract_color(Regex::new(r"(\d+) red"), raw_extraction);
-        Extraction { red, blue, green }
-    }
-
-    fn parse_extractions(raw_extractions: &str) -> Vec<Self> {
+    pub fn parse_extraction(line: &Line, raw_extraction: &str) -> Result<Self, ParseError> {
+        let mut extraction = Extraction { red: None, blue: None, green: None };
+        for cubes in raw_extraction.split(", ") {
+            let (count, color) = line.split_once(cubes, " ")?;
+            let count = Some(line.parse::<usize>(count)?);
+            match color {
+                "red" => extraction.red = count,
+                "blue" => extraction.blue = count,
+                "green" => extraction.green = count,
+                _ => return Err(line.error(color, format!("unexpected color `{}`", color)))
+            }
+        }
+        Ok(extraction)
+    }
+
+    fn parse_extractions(line: &Line, raw_extractions: &str) -> Result<Vec<Self>, ParseError> {
         raw_extractions.split("; ")
-            .collect::<Vec<_>>().iter()
-            .map(|e| Extraction::parse_extraction(e))
+            .map(|e| Extraction::parse_extraction(line, e))
             .collect()
     }
 
-    fn is_possible(&self) -> bool {
+    pub fn is_possible(&self) -> bool {
         self.is_possible_blue() && self.is_possible_green() && self.is_possible_red()
     }
 
@@ -97,28 +104,42 @@ fn is_possible_color(color: Option<usize>, expected: usize) -> bool {
     true
 }
 
-fn parse_game_id(game_with_id: &str) -> usize {
-    Regex::new(r"Game (\d+)").unwrap()
+fn parse_game_id(line: &Line, game_with_id: &str) -> Result<usize, ParseError> {
+    Regex::new(r"^Game (\d+)$").unwrap()
         .captures(game_with_id)
-        .map(|c| c[1].parse::<usize>().unwrap())
-        .unwrap()
-}
-
-fn extract_color(color_regex: Result<Regex, Error>, raw_extraction: &str) -> Option<usize> {
-    color_regex.unwrap().captures(raw_extraction).map(|c| c[1].parse::<usize>().unwrap())
+        .ok_or_else(|| line.error(game_with_id, format!("expected `Game <id>`, found `{}`", game_with_id)))
+        .and_then(|c| line.parse::<usize>(c.get(1).unwrap().as_str()))
 }
 
-fn calculate_possible_games_ids_sum(lines: &str) -> usize {
-    Game::parse_games(read_lines(lines)).iter()
+pub fn calculate_possible_games_ids_sum(lines: &str) -> Result<usize, ParseError> {
+    let sum = Game::parse_games(lines)?.iter()
         .filter(|game| game.is_possible())
         .map(|game| game.id)
-        .sum()
+        .sum();
+    Ok(sum)
 }
 
-fn calculate_power_sum(lines: &str) -> usize {
-    Game::parse_games(read_lines(lines)).iter()
+pub fn calculate_power_sum(lines: &str) -> Result<usize, ParseError> {
+    let sum = Game::parse_games(lines)?.iter()
         .map(|game| game.power())
-        .sum()
+        .sum();
+    Ok(sum)
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(Game::parse_games(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_possible_games_ids_sum(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_power_sum(input)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -130,13 +151,13 @@ mod tests {
     #[test]
     fn it_solves_first_part() {
         let input = read_input_file("input_day02.txt");
-        assert_eq!(2528, calculate_possible_games_ids_sum(&input));
+        assert_eq!(Ok(2528), calculate_possible_games_ids_sum(&input));
     }
 
     #[test]
     fn it_solves_second_part() {
         let input = read_input_file("input_day02.txt");
-        assert_eq!(67363, calculate_power_sum(&input));
+        assert_eq!(Ok(67363), calculate_power_sum(&input));
     }
 
     #[test]
@@ -148,7 +169,7 @@ mod tests {
         Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
         Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"};
 
-        assert_eq!(2286, calculate_power_sum(input))
+        assert_eq!(Ok(2286), calculate_power_sum(input))
     }
 
     #[test]
@@ -160,16 +181,28 @@ mod tests {
         Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
         Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green"};
 
-        assert_eq!(8, calculate_possible_games_ids_sum(input));
+        assert_eq!(Ok(8), calculate_possible_games_ids_sum(input));
     }
 
     #[test]
     fn it_finds_if_a_game_is_possible() {
-        assert!(Game::parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").is_possible());
-        assert!(Game::parse_game("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue").is_possible());
-        assert_eq!(false, Game::parse_game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").is_possible());
-        assert_eq!(false, Game::parse_game("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").is_possible());
-        assert!(Game::parse_game("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green").is_possible());
+        assert!(parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").is_possible());
+        assert!(parse_game("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue").is_possible());
+        assert!(!parse_game("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").is_possible());
+        assert!(!parse_game("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").is_possible());
+        assert!(parse_game("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green").is_possible());
+    }
+
+    #[test]
+    fn it_reports_a_malformed_game() {
+        assert_eq!(
+            Err(ParseError::new(2, 2, 11, "unexpected color `purple`")),
+            Game::parse_games("Game 1: 3 blue\nGame 2: 1 purple")
+        );
+        assert_eq!(
+            Err(ParseError::new(2, 1, 1, "expected `Game <id>`, found `Gam 1`")),
+            Game::parse_games("Gam 1: 3 blue")
+        );
     }
 
     #[test]
@@ -182,7 +215,11 @@ mod tests {
                     Extraction { red: Some(1), blue: Some(6), green: Some(2) },
                     Extraction { red: None, green: Some(2), blue: None },
                 ],
-            }, Game::parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green")
+            }, parse_game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green")
         );
     }
+
+    fn parse_game(raw_game: &str) -> Game {
+        Game::parse_game(&Line::new(2, 1, raw_game)).unwrap()
+    }
 }
\ No newline at end of file
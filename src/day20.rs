@@ -1,34 +1,629 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use itertools::Itertools;
+use num::integer::lcm;
+
+use crate::day20::Pulse::{High, Low};
+use crate::parse_error::{lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-enum Pulse {
+pub enum Pulse {
     Low,
     High,
 }
 
+// a gate and the modules it sends its pulses to
+#[derive(Debug, Clone)]
+pub struct Module {
+    gate: Box<dyn Gate>,
+    outputs: Vec<String>,
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        self.gate.eq_box(other.gate.as_ref()) && self.outputs == other.outputs
+    }
+}
+
+impl Eq for Module {}
+
+impl Module {
+    pub fn new(gate: impl Gate + 'static, outputs: &[&str]) -> Self {
+        Module { gate: Box::new(gate), outputs: outputs.iter().map(|output| output.to_string()).collect() }
+    }
+
+    pub fn outputs(&self) -> &[String] {
+        &self.outputs
+    }
+
+    // the gate of the module, when it is a `G`
+    pub fn gate<G: Gate + 'static>(&self) -> Option<&G> {
+        self.gate.as_any().downcast_ref()
+    }
+}
+
+// the behaviour of a module, see `built_in_gate` for the ones available beyond the puzzle ones
+pub trait Gate: GateBox + fmt::Debug {
+    // the characters before the module name in the configuration
+    fn prefix(&self) -> &str;
+
+    // returns the pulse sent to every output, if any
+    fn receive(&mut self, source: &str, pulse: Pulse) -> Option<Pulse>;
+
+    // called for every module sending pulses to this one, before the first press
+    fn connect(&mut self, _input: &str) {}
+
+    // shown in the exports
+    fn state(&self) -> String {
+        String::new()
+    }
+}
+
+// what a boxed gate needs, implemented for every gate that can be cloned and compared
+pub trait GateBox {
+    fn clone_box(&self) -> Box<dyn Gate>;
+
+    // whether `other` is the same kind of gate in the same state
+    fn eq_box(&self, other: &dyn Gate) -> bool;
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Gate + Clone + PartialEq + 'static> GateBox for T {
+    fn clone_box(&self) -> Box<dyn Gate> {
+        Box::new(self.clone())
+    }
+
+    fn eq_box(&self, other: &dyn Gate) -> bool {
+        other.as_any().downcast_ref::<Self>() == Some(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn Gate> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+// sends every pulse it receives to all its outputs
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Broadcaster;
+
+impl Gate for Broadcaster {
+    fn prefix(&self) -> &str {
+        ""
+    }
+
+    fn receive(&mut self, _source: &str, pulse: Pulse) -> Option<Pulse> {
+        Some(pulse)
+    }
+}
+
+// ignores high pulses, a low pulse turns it on, sending a high pulse, or off, sending a low one
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FlipFlop {
+    pub on: bool,
+}
+
+impl Gate for FlipFlop {
+    fn prefix(&self) -> &str {
+        "%"
+    }
+
+    fn receive(&mut self, _source: &str, pulse: Pulse) -> Option<Pulse> {
+        if pulse == High {
+            return None;
+        }
+
+        self.on = !self.on;
+        Some(if self.on { High } else { Low })
+    }
+
+    fn state(&self) -> String {
+        if self.on { "on".to_string() } else { "off".to_string() }
+    }
+}
+
+// remembers the last pulse of every input, sends a low pulse when they are all high, a high one otherwise
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Conjunction {
+    pub memory: HashMap<String, Pulse>,
+}
+
+impl Gate for Conjunction {
+    fn prefix(&self) -> &str {
+        "&"
+    }
+
+    fn receive(&mut self, source: &str, pulse: Pulse) -> Option<Pulse> {
+        self.memory.insert(source.to_string(), pulse);
+        Some(if self.memory.values().all(|&p| p == High) { Low } else { High })
+    }
+
+    fn connect(&mut self, input: &str) {
+        self.memory.insert(input.to_string(), Low);
+    }
+
+    fn state(&self) -> String {
+        self.memory.iter()
+            .sorted_by_key(|(input, _)| *input)
+            .map(|(input, pulse)| format!("{}={:?}", input, pulse))
+            .join(" ")
+    }
+}
+
+// sends the opposite of every pulse it receives
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Inverter;
+
+impl Gate for Inverter {
+    fn prefix(&self) -> &str {
+        "!"
+    }
+
+    fn receive(&mut self, _source: &str, pulse: Pulse) -> Option<Pulse> {
+        Some(if pulse == High { Low } else { High })
+    }
+}
+
+// holds every pulse it receives until the next one, then sends it
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Delay {
+    pub held: Option<Pulse>,
+}
+
+impl Gate for Delay {
+    fn prefix(&self) -> &str {
+        "="
+    }
+
+    fn receive(&mut self, _source: &str, pulse: Pulse) -> Option<Pulse> {
+        self.held.replace(pulse)
+    }
+
+    fn state(&self) -> String {
+        self.held.map_or("empty".to_string(), |pulse| format!("holding {:?}", pulse))
+    }
+}
+
+// counts the low pulses modulo 2^bits, ignoring the high ones: it sends a high pulse when it wraps around to 0,
+// a low one otherwise
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Counter {
+    pub bits: u32,
+    pub count: usize,
+}
+
+impl Gate for Counter {
+    fn prefix(&self) -> &str {
+        "#"
+    }
+
+    fn receive(&mut self, _source: &str, pulse: Pulse) -> Option<Pulse> {
+        if pulse == High {
+            return None;
+        }
+
+        self.count = (self.count + 1) % (1 << self.bits);
+        Some(if self.count == 0 { High } else { Low })
+    }
+
+    fn state(&self) -> String {
+        format!("{}/{}", self.count, 1usize << self.bits)
+    }
+}
+
+// records every pulse it receives and sends nothing
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Sink {
+    pub received: Vec<(String, Pulse)>,
+}
+
+impl Gate for Sink {
+    fn prefix(&self) -> &str {
+        "@"
+    }
+
+    fn receive(&mut self, source: &str, pulse: Pulse) -> Option<Pulse> {
+        self.received.push((source.to_string(), pulse));
+        None
+    }
+
+    fn state(&self) -> String {
+        format!("{} received", self.received.len())
+    }
+}
+
+// `!inv`, `=delay`, `#<bits>counter` like `#4div` and `@sink`, returns the module name and its gate
+pub(crate) fn built_in_gate(prefix: char, text: &str) -> Option<(String, Box<dyn Gate>)> {
+    let gate: Box<dyn Gate> = match prefix {
+        '!' => Box::new(Inverter),
+        '=' => Box::new(Delay::default()),
+        '@' => Box::new(Sink::default()),
+        '#' => {
+            let name_start = text.find(|c: char| !c.is_ascii_digit())?;
+            let bits = text[..name_start].parse().ok().filter(|bits| (1..usize::BITS).contains(bits))?;
+            return Some((text[name_start..].to_string(), Box::new(Counter { bits, count: 0 })));
+        }
+        _ => return None
+    };
+    Some((text.to_string(), gate))
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
-enum Modules {
-    FlipFlop(bool, Vec<String>),
-    Conjunction(HashMap<String, Pulse>, Vec<String>),
-    Broadcaster(Vec<String>),
+pub struct PulseEvent {
+    pub source: String,
+    pub destination: String,
+    pub pulse: Pulse,
+}
+
+impl PulseEvent {
+    fn new(source: &str, destination: &str, pulse: Pulse) -> Self {
+        PulseEvent { source: source.to_string(), destination: destination.to_string(), pulse }
+    }
+}
+
+// the pulses a module has sent and received since the last reset
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct PulseCounters {
+    pub low_sent: usize,
+    pub high_sent: usize,
+    pub low_received: usize,
+    pub high_received: usize,
+}
+
+// the state of every module after a number of button presses
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Snapshot {
+    pub presses: usize,
+    pub modules: HashMap<String, Module>,
+}
+
+pub struct Network {
+    initial: HashMap<String, Module>,
+    modules: HashMap<String, Module>,
+    presses: usize,
+    counters: HashMap<String, PulseCounters>,
+}
+
+impl Network {
+    pub fn new(configuration: HashMap<String, Module>) -> Self {
+        Network { initial: configuration.clone(), modules: configuration, presses: 0, counters: HashMap::new() }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Network::new(parse_configuration(input)?))
+    }
+
+    // pushes the button once and returns every pulse in the order it is processed, the button one first
+    pub fn press(&mut self) -> Vec<PulseEvent> {
+        self.presses += 1;
+        let mut events = vec![];
+        let mut sequence = VecDeque::from([PulseEvent::new("button", "broadcaster", Low)]);
+        while let Some(event) = sequence.pop_front() {
+            self.count(&event);
+            if let Some(module) = self.modules.get_mut(&event.destination) {
+                if let Some(pulse) = module.gate.receive(&event.source, event.pulse) {
+                    for o in &module.outputs {
+                        sequence.push_back(PulseEvent::new(&event.destination, o, pulse))
+                    }
+                }
+            }
+            events.push(event);
+        }
+        events
+    }
+
+    fn count(&mut self, event: &PulseEvent) {
+        let sent = self.counters.entry(event.source.clone()).or_default();
+        match event.pulse {
+            Low => sent.low_sent += 1,
+            High => sent.high_sent += 1,
+        }
+        let received = self.counters.entry(event.destination.clone()).or_default();
+        match event.pulse {
+            Low => received.low_received += 1,
+            High => received.high_received += 1,
+        }
+    }
+
+    pub fn presses(&self) -> usize {
+        self.presses
+    }
+
+    pub fn module(&self, name: &str) -> Option<&Module> {
+        self.modules.get(name)
+    }
+
+    // `button` and the unconfigured destinations, like `rx`, have counters too
+    pub fn counters(&self, name: &str) -> PulseCounters {
+        self.counters.get(name).copied().unwrap_or_default()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot { presses: self.presses, modules: self.modules.clone() }
+    }
+
+    // the counters are kept, they keep counting from the restored state
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.presses = snapshot.presses;
+        self.modules = snapshot.modules.clone();
+    }
+
+    // back to the state before the first press
+    pub fn reset(&mut self) {
+        self.modules = self.initial.clone();
+        self.presses = 0;
+        self.counters.clear();
+    }
+}
+
+// a cycle is only trusted after it has been seen that many times
+const CYCLE_REPETITIONS: usize = 3;
+const MAX_PRESSES: usize = 100_000;
+
+// the high pulses `source` sends to the conjunction feeding `rx` repeat every `period` presses, starting from the first
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cycle {
+    pub source: String,
+    pub period: usize,
+}
+
+// the only module sending pulses to `rx` must be a conjunction, its inputs are returned sorted
+pub fn rx_feeder(configuration: &HashMap<String, Module>) -> Option<(String, Vec<String>)> {
+    let feeders: Vec<_> = configuration.iter()
+        .filter(|(_, module)| module.outputs().contains(&"rx".to_string()))
+        .collect();
+
+    match feeders[..] {
+        [(name, module)] => module.gate::<Conjunction>().map(|conjunction| (name.clone(), conjunction.memory.keys().cloned().sorted().collect())),
+        _ => None
+    }
+}
+
+// presses the button until every source has sent CYCLE_REPETITIONS high pulses to `destination`,
+// a cycle that does not start at its period, or whose gaps change, is reported.
+// Presses are counted from the call, whatever the network went through before.
+pub fn find_cycles(network: &mut Network, sources: &[String], destination: &str) -> Result<Vec<Cycle>, String> {
+    let mut high_pulses: HashMap<&str, Vec<usize>> = sources.iter().map(|source| (source.as_str(), vec![])).collect();
+    let mut presses = 0;
+    while high_pulses.values().any(|presses| presses.len() < CYCLE_REPETITIONS) {
+        if presses == MAX_PRESSES {
+            return Err(format!("high pulses to `{}` do not repeat within {} presses", destination, MAX_PRESSES));
+        }
+
+        presses += 1;
+        for event in network.press() {
+            if event.destination == destination && event.pulse == High {
+                if let Some(source_presses) = high_pulses.get_mut(event.source.as_str()) {
+                    source_presses.push(presses);
+                }
+            }
+        }
+    }
+
+    sources.iter()
+        .map(|source| {
+            let presses = &high_pulses[source.as_str()];
+            let period = presses[0];
+            if presses.iter().enumerate().all(|(i, &press)| press == (i + 1) * period) {
+                Ok(Cycle { source: source.clone(), period })
+            } else {
+                Err(format!("high pulses from `{}` to `{}` are not a pure cycle, they are sent at presses {:?}", source, destination, presses))
+            }
+        })
+        .collect()
+}
+
+// the machine is turned on when the conjunction feeding `rx` sends a low pulse,
+// that happens when all its inputs have sent a high pulse during the same button press
+pub fn steps_to_activate_rx(input: &str) -> Result<usize, SolveError> {
+    let configuration = parse_configuration(input)?;
+    let (feeder, inputs) = rx_feeder(&configuration)
+        .ok_or_else(|| SolveError::unsolvable(20, "`rx` is not fed by a single conjunction"))?;
+
+    let cycles = find_cycles(&mut Network::new(configuration), &inputs, &feeder)
+        .map_err(|reason| SolveError::unsolvable(20, reason))?;
+    cycles.into_iter()
+        .map(|cycle| cycle.period)
+        .reduce(lcm)
+        .ok_or_else(|| SolveError::unsolvable(20, format!("conjunction `{}` feeding `rx` has no inputs", feeder)))
+}
+
+pub fn count_pulses(network: &mut Network, presses: usize) -> (usize, usize) {
+    let mut low_pulses: usize = 0;
+    let mut high_pulses: usize = 0;
+
+    for _ in 0..presses {
+        for event in network.press() {
+            if event.pulse == Low {
+                low_pulses += 1;
+            } else {
+                high_pulses += 1;
+            }
+        }
+    }
+    (low_pulses, high_pulses)
+}
+
+pub fn parse_configuration(input: &str) -> Result<HashMap<String, Module>, ParseError> {
+    parse_configuration_with(input, built_in_gate)
+}
+
+// `gate` builds the modules whose prefix is not `%` or `&` from their prefix and the text after it,
+// a line without ` -> ` is a module without outputs
+pub fn parse_configuration_with(input: &str, gate: impl Fn(char, &str) -> Option<(String, Box<dyn Gate>)>) -> Result<HashMap<String, Module>, ParseError> {
+    let mut configuration = HashMap::new();
+    let mut inputs: HashMap<String, Vec<String>> = HashMap::new();
+
+    for line in lines(20, input) {
+        let (raw_module, raw_outputs) = line.text.split_once(" -> ").unwrap_or((line.text, ""));
+        let outputs = raw_outputs.split(", ").filter(|s| !s.is_empty()).map(|s| s.to_string()).collect::<Vec<_>>();
+        if raw_module == "broadcaster" {
+            for o in &outputs {
+                inputs.entry(o.to_string()).or_insert(vec![]);
+                inputs.get_mut(o).unwrap().push("broadcaster".to_string());
+            }
+            configuration.insert(raw_module.to_string(), Module { gate: Box::new(Broadcaster), outputs });
+            continue
+        }
+
+        let mut module = raw_module.to_string();
+        if module.len() < 2 {
+            return Err(line.error(raw_module, format!("expected a module name, found `{}`", raw_module)));
+        }
+
+        let (name, gate): (String, Box<dyn Gate>) = match module.remove(0) {
+            '%' => (module, Box::new(FlipFlop::default())),
+            '&' => (module, Box::new(Conjunction::default())),
+            prefix => gate(prefix, &module)
+                .ok_or_else(|| line.error(raw_module, format!("unexpected module type `{}`", prefix)))?
+        };
+        configuration.insert(name.clone(), Module { gate, outputs: outputs.clone() });
+
+        for o in &outputs {
+            inputs.entry(o.to_string()).or_insert(vec![]);
+            inputs.get_mut(o).unwrap().push(name.clone());
+        }
+    }
+
+    for (name, module) in configuration.iter_mut() {
+        for i in inputs.get(name).into_iter().flatten() {
+            module.gate.connect(i);
+        }
+    }
+    Ok(configuration)
+}
+
+// modules are styled by kind, the destinations without a module, like `rx`, are drawn as sinks.
+// With `show_state` the labels also show the flip-flop states and the conjunction memories.
+pub fn to_dot(configuration: &HashMap<String, Module>, show_state: bool) -> String {
+    let mut dot = vec!["digraph modules {".to_string()];
+    for name in node_names(configuration) {
+        let attributes = match configuration.get(&name) {
+            Some(module) => {
+                let shape = match kind(module) {
+                    "broadcaster" => "doubleoctagon",
+                    "flipflop" => "box",
+                    "conjunction" => "invhouse",
+                    _ => "ellipse",
+                };
+                format!("shape={}, label=\"{}\"", shape, label(&name, module, show_state, "\\n"))
+            }
+            None => "shape=plaintext".to_string(),
+        };
+        dot.push(format!("  \"{}\" [{}];", name, attributes));
+    }
+    for (source, destination) in edges(configuration) {
+        dot.push(format!("  \"{}\" -> \"{}\";", source, destination));
+    }
+    dot.push("}".to_string());
+    dot.join("\n")
+}
+
+pub fn to_mermaid(configuration: &HashMap<String, Module>, show_state: bool) -> String {
+    let mut mermaid = vec!["flowchart LR".to_string()];
+    for name in node_names(configuration) {
+        let node = match configuration.get(&name) {
+            Some(module) => {
+                let label = label(&name, module, show_state, "<br>");
+                match kind(module) {
+                    "broadcaster" => format!("(((\"{}\"))):::broadcaster", label),
+                    "flipflop" => format!("[\"{}\"]:::flipflop", label),
+                    "conjunction" => format!("{{{{\"{}\"}}}}:::conjunction", label),
+                    kind => format!("(\"{}\"):::{}", label, kind),
+                }
+            }
+            None => format!("([\"{}\"]):::sink", name),
+        };
+        mermaid.push(format!("  {}{}", name, node));
+    }
+    for (source, destination) in edges(configuration) {
+        mermaid.push(format!("  {} --> {}", source, destination));
+    }
+    mermaid.push("  classDef broadcaster fill:#ffd966".to_string());
+    mermaid.push("  classDef flipflop fill:#9fc5e8".to_string());
+    mermaid.push("  classDef conjunction fill:#b6d7a8".to_string());
+    mermaid.push("  classDef gate fill:#d5a6bd".to_string());
+    mermaid.push("  classDef sink fill:#eeeeee".to_string());
+    mermaid.join("\n")
+}
+
+// every module and destination, sorted so that exports are stable
+fn node_names(configuration: &HashMap<String, Module>) -> Vec<String> {
+    configuration.iter()
+        .flat_map(|(name, module)| std::iter::once(name).chain(module.outputs()))
+        .cloned()
+        .sorted()
+        .dedup()
+        .collect()
+}
+
+fn edges(configuration: &HashMap<String, Module>) -> Vec<(&str, &str)> {
+    configuration.iter()
+        .sorted_by_key(|(name, _)| *name)
+        .flat_map(|(name, module)| module.outputs().iter().map(move |output| (name.as_str(), output.as_str())))
+        .collect()
+}
+
+// the puzzle modules are styled apart from the other gates
+fn kind(module: &Module) -> &'static str {
+    if module.gate::<Broadcaster>().is_some() {
+        "broadcaster"
+    } else if module.gate::<FlipFlop>().is_some() {
+        "flipflop"
+    } else if module.gate::<Conjunction>().is_some() {
+        "conjunction"
+    } else {
+        "gate"
+    }
+}
+
+fn label(name: &str, module: &Module, show_state: bool, line_break: &str) -> String {
+    let state = module.gate.state();
+    if show_state && !state.is_empty() {
+        format!("{}{}{}{}", module.gate.prefix(), name, line_break, state)
+    } else {
+        format!("{}{}", module.gate.prefix(), name)
+    }
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_configuration(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        let (low_pulses, high_pulses) = count_pulses(&mut Network::parse(input)?, 1000);
+        Ok((low_pulses * high_pulses).into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(steps_to_activate_rx(input)?.into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashMap, LinkedList};
     use indoc::indoc;
-    use num::integer::lcm;
-    use crate::day20::Modules::{Broadcaster, Conjunction, FlipFlop};
-    use crate::day20::{Modules, Pulse};
-    use crate::day20::Pulse::{High, Low};
-    use crate::input_reader::{read_input_file, read_lines};
+
+    use crate::day20::*;
+    use crate::input_reader::read_input_file;
 
     #[test]
     fn it_solves_first_part() {
         let input = &read_input_file("input_day20.txt");
 
-        let mut configuration = parse_configuration(input);
-        let (low_pulses, high_pulses) = count_pulses(&mut configuration);
+        let (low_pulses, high_pulses) = count_pulses(&mut Network::parse(input).unwrap(), 1000);
         assert_eq!(743871576, low_pulses * high_pulses);
     }
 
@@ -36,14 +631,52 @@ mod tests {
     fn it_solves_second_part() {
         let input = &read_input_file("input_day20.txt");
 
-        // manual hack based on my input
-        assert_eq!(3907, steps_to_turn_on_the_machine(&mut parse_configuration(input), "ph".to_string(), "kc".to_string()));
-        assert_eq!(3797, steps_to_turn_on_the_machine(&mut parse_configuration(input), "vn".to_string(), "kc".to_string()));
-        assert_eq!(4093, steps_to_turn_on_the_machine(&mut parse_configuration(input), "kt".to_string(), "kc".to_string()));
-        assert_eq!(4021, steps_to_turn_on_the_machine(&mut parse_configuration(input), "hn".to_string(), "kc".to_string()));
-        let values:[usize; 4] = [3907, 3797, 4093, 4021];
-        let result= values.into_iter().reduce(|a, b| lcm(a, b)).unwrap();
-        assert_eq!(244151741342687, result)
+        let configuration = parse_configuration(input).unwrap();
+        let (feeder, inputs) = rx_feeder(&configuration).unwrap();
+        assert_eq!(("kc".to_string(), vec!["hn", "kt", "ph", "vn"]), (feeder.clone(), inputs.iter().map(String::as_str).collect()));
+        let periods: Vec<_> = find_cycles(&mut Network::new(configuration), &inputs, &feeder).unwrap()
+            .into_iter()
+            .map(|cycle| cycle.period)
+            .collect();
+        assert_eq!(vec![4021, 4093, 3907, 3797], periods);
+        assert_eq!(Ok(244151741342687), steps_to_activate_rx(input));
+    }
+
+    #[test]
+    fn it_detects_cycles() {
+        let input: &str = indoc! {"
+        broadcaster -> a, b
+        %a -> ia
+        &ia -> x
+        %b -> c
+        %c -> ic
+        &ic -> x
+        &x -> rx"};
+
+        let mut network = Network::parse(input).unwrap();
+        let sources = ["ia".to_string(), "ic".to_string()];
+        let cycles = vec![Cycle { source: "ia".to_string(), period: 2 }, Cycle { source: "ic".to_string(), period: 4 }];
+        assert_eq!(Ok(cycles.clone()), find_cycles(&mut network, &sources, "x"));
+        assert_eq!(Ok(4), steps_to_activate_rx(input));
+
+        // the presses before the search are not counted, the flip-flops are back where they started
+        assert_eq!(Ok(cycles), find_cycles(&mut network, &sources, "x"));
+        (0..MAX_PRESSES).for_each(|_| { network.press(); });
+        assert_eq!(
+            Err(format!("high pulses to `x` do not repeat within {} presses", MAX_PRESSES)),
+            find_cycles(&mut network, &["a".to_string()], "x")
+        );
+
+        // `c` is on after 2 presses, off after 4, on again after 6
+        let shifted = input.replace("%c -> ic\n&ic -> x", "%c -> x");
+        assert_eq!(
+            Err(SolveError::unsolvable(20, "high pulses from `c` to `x` are not a pure cycle, they are sent at presses [2, 6, 10]")),
+            steps_to_activate_rx(&shifted)
+        );
+        assert_eq!(
+            Err(SolveError::unsolvable(20, "`rx` is not fed by a single conjunction")),
+            steps_to_activate_rx(&input.replace("%a -> ia", "%a -> ia, rx"))
+        );
     }
 
     #[test]
@@ -62,147 +695,189 @@ mod tests {
         %b -> con
         &con -> output"};
 
-        let mut configuration = parse_configuration(input);
-
-        let (low_pulses, high_pulses) = count_pulses(&mut configuration);
+        let (low_pulses, high_pulses) = count_pulses(&mut Network::parse(input).unwrap(), 1000);
         assert_eq!(11687500, low_pulses * high_pulses);
     }
 
-    fn steps_to_turn_on_the_machine(configuration: &mut HashMap<String, Modules>, s: String, d: String) -> usize {
-        let mut steps_to_turn_on_the_machine: usize = 0;
+    #[test]
+    fn it_traces_every_press() {
+        let input: &str = indoc! {"
+        broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output"};
 
-        loop {
-            steps_to_turn_on_the_machine += 1;
-            let mut sequence: LinkedList<(String, String, Pulse)> = LinkedList::new();
-            sequence.push_back(("button".to_string(), "broadcaster".to_string(), Low));
-            while let Some((source, destination, pulse)) = sequence.pop_front() {
-                if source == s && destination == d && pulse == High {
-                    return steps_to_turn_on_the_machine
-                }
+        let mut network = Network::parse(input).unwrap();
+        let initial = network.snapshot();
+        let trace: Vec<_> = network.press().iter()
+            .map(|event| format!("{} -{:?}-> {}", event.source, event.pulse, event.destination))
+            .collect();
+        assert_eq!(vec![
+            "button -Low-> broadcaster",
+            "broadcaster -Low-> a",
+            "a -High-> inv",
+            "a -High-> con",
+            "inv -Low-> b",
+            "con -High-> output",
+            "b -High-> con",
+            "con -Low-> output",
+        ], trace);
+        assert_eq!(Some(&Module::new(FlipFlop { on: true }, &["con"])), network.module("b"));
+        assert_eq!(PulseCounters { low_sent: 1, high_sent: 1, low_received: 0, high_received: 2 }, network.counters("con"));
+        assert_eq!(PulseCounters { low_sent: 0, high_sent: 0, low_received: 1, high_received: 1 }, network.counters("output"));
 
-                if !configuration.contains_key(&destination) {
-                    continue
-                }
-                match configuration.get_mut(&destination).unwrap() {
-                    FlipFlop(state, outputs) => handle_flip_flop(&mut sequence, pulse, state, outputs, &destination),
-                    Conjunction(inputs, outputs) => handling_conjunctions(&mut sequence, inputs, outputs, source, &destination, pulse),
-                    Broadcaster(outputs) => handle_broadcaster(&mut sequence, outputs, pulse)
-                }
-            }
-        }
+        let after_one = network.snapshot();
+        assert_eq!(6, network.press().len());
+        network.restore(&after_one);
+        assert_eq!((1, after_one.modules.clone()), (network.presses(), network.snapshot().modules));
+
+        network.reset();
+        assert_eq!(initial, network.snapshot());
+        assert_eq!(PulseCounters::default(), network.counters("con"));
     }
 
-    fn count_pulses(configuration: &mut HashMap<String, Modules>) -> (usize, usize) {
-        let mut low_pulses: usize = 0;
-        let mut high_pulses: usize = 0;
+    #[test]
+    fn it_exports_the_configuration() {
+        let input: &str = indoc! {"
+        broadcaster -> a
+        %a -> inv, con
+        &inv -> b
+        %b -> con
+        &con -> output"};
 
-        for _ in 0..1000 {
-            let mut sequence: LinkedList<(String, String, Pulse)> = LinkedList::new();
-            sequence.push_back(("button".to_string(), "broadcaster".to_string(), Low));
-            while let Some((source, destination, pulse)) = sequence.pop_front() {
-                if pulse == Low {
-                    low_pulses += 1;
-                } else {
-                    high_pulses += 1;
-                }
+        let mut network = Network::parse(input).unwrap();
+        let expected = indoc! {r#"
+        digraph modules {
+          "a" [shape=box, label="%a"];
+          "b" [shape=box, label="%b"];
+          "broadcaster" [shape=doubleoctagon, label="broadcaster"];
+          "con" [shape=invhouse, label="&con"];
+          "inv" [shape=invhouse, label="&inv"];
+          "output" [shape=plaintext];
+          "a" -> "inv";
+          "a" -> "con";
+          "b" -> "con";
+          "broadcaster" -> "a";
+          "con" -> "output";
+          "inv" -> "b";
+        }"#};
+        assert_eq!(expected, to_dot(&network.snapshot().modules, false));
 
-                if !configuration.contains_key(&destination) {
-                    continue
-                }
-                match configuration.get_mut(&destination).unwrap() {
-                    FlipFlop(state, outputs) => handle_flip_flop(&mut sequence, pulse, state, outputs, &destination),
-                    Conjunction(inputs, outputs) => handling_conjunctions(&mut sequence, inputs, outputs, source, &destination, pulse),
-                    Broadcaster(outputs) => handle_broadcaster(&mut sequence, outputs, pulse)
-                }
-            }
-        }
-        (low_pulses, high_pulses)
+        network.press();
+        let expected = indoc! {r#"
+        flowchart LR
+          a["%a<br>on"]:::flipflop
+          b["%b<br>on"]:::flipflop
+          broadcaster((("broadcaster"))):::broadcaster
+          con{{"&con<br>a=High b=High"}}:::conjunction
+          inv{{"&inv<br>a=High"}}:::conjunction
+          output(["output"]):::sink
+          a --> inv
+          a --> con
+          b --> con
+          broadcaster --> a
+          con --> output
+          inv --> b
+          classDef broadcaster fill:#ffd966
+          classDef flipflop fill:#9fc5e8
+          classDef conjunction fill:#b6d7a8
+          classDef gate fill:#d5a6bd
+          classDef sink fill:#eeeeee"#};
+        assert_eq!(expected, to_mermaid(&network.snapshot().modules, true));
+        assert!(to_dot(&network.snapshot().modules, true).contains(r#""con" [shape=invhouse, label="&con\na=High b=High"];"#));
     }
 
-    fn parse_configuration(input: &str) -> HashMap<String, Modules> {
-        let mut configuration = HashMap::new();
-        let mut inputs: HashMap<String, Vec<String>> = HashMap::new();
-        let mut conjuctions: Vec<String> = vec![];
+    #[test]
+    fn it_runs_custom_gates() {
+        let input: &str = indoc! {"
+        broadcaster -> inv, cnt, del
+        !inv -> log
+        #2cnt -> log
+        =del -> log
+        @log"};
 
-        for line in read_lines(input) {
-            let parts = line.split(" -> ").collect::<Vec<_>>();
-            let outputs = parts[1].split(", ").map(|s| s.to_string()).collect::<Vec<_>>();
-            if parts[0] == "broadcaster" {
-                for o in &outputs {
-                    inputs.entry(o.to_string()).or_insert(vec![]);
-                    inputs.get_mut(o).unwrap().push("broadcaster".to_string());
-                }
-                configuration.insert(parts[0].to_string(), Broadcaster(outputs));
-                continue
-            }
+        let mut network = Network::parse(input).unwrap();
+        let received = |network: &Network| network.module("log").unwrap().gate::<Sink>().unwrap().received.clone();
+        network.press();
+        assert_eq!(vec![("inv".to_string(), High), ("cnt".to_string(), Low)], received(&network));
+        assert_eq!(Some(&Delay { held: Some(Low) }), network.module("del").unwrap().gate::<Delay>());
 
-            let mut module = parts[0].to_string();
-
-            match module.remove(0) {
-                '%' => {
-                    configuration.insert(module.clone(), FlipFlop(false, outputs.clone()));
-                },
-                '&' => {
-                    configuration.insert(module.clone(), Conjunction(HashMap::new(), outputs.clone()));
-                    conjuctions.push(module.clone());
-                },
-                _ => panic!("not expected")
-            }
+        (0..3).for_each(|_| { network.press(); });
+        assert_eq!(vec![("inv".to_string(), High), ("cnt".to_string(), High), ("del".to_string(), Low)], received(&network)[8..]);
+        assert_eq!(0, network.module("cnt").unwrap().gate::<Counter>().unwrap().count);
+        assert!(to_dot(&network.snapshot().modules, true).contains(r#""log" [shape=ellipse, label="@log\n11 received"];"#));
 
-            for o in &outputs {
-                inputs.entry(o.to_string()).or_insert(vec![]);
-                inputs.get_mut(o).unwrap().push(module.clone());
-            }
-        }
+        network.reset();
+        assert_eq!(Some(&Module::new(Sink::default(), &[])), network.module("log"));
 
-        for c in &conjuctions {
-            match configuration.get_mut(c).unwrap() {
-                Conjunction(ci, _) => {
-                    for i in inputs.get(c).unwrap() {
-                        ci.insert(i.clone(), Low);
-                    }
-                },
-                _ => panic!("not expected")
-            }
-        }
-        configuration
-    }
+        // sinks are equal when they received the same pulses, not just as many
+        let sink = |pulse: Pulse| Module::new(Sink { received: vec![("inv".to_string(), pulse)] }, &[]);
+        assert_ne!(sink(Low), sink(High));
+        assert_eq!(sink(Low), sink(Low));
 
-    fn handling_conjunctions(sequence: &mut LinkedList<(String, String, Pulse)>, inputs: &mut HashMap<String, Pulse>, outputs: &Vec<String>, source: String, conjunction_id: &String, pulse: Pulse) {
-        inputs.insert(source, pulse);
+        // a delay sends the pulse it received before
+        let mut delay = Delay::default();
+        assert_eq!(vec![None, Some(Low), Some(High)], [Low, High, Low].map(|pulse| delay.receive("a", pulse)).to_vec());
+        assert_eq!("holding Low", delay.state());
+    }
 
-        let pulse_to_send = if inputs.values().all(|&p| p == High) {
-            Low
-        } else {
-            High
-        };
+    // remembers whether all its inputs sent a high pulse last
+    #[derive(Debug, Clone, Default, Eq, PartialEq)]
+    struct And(HashMap<String, Pulse>);
 
-        for o in outputs {
-            sequence.push_back((conjunction_id.clone(), o.clone(), pulse_to_send))
+    impl Gate for And {
+        fn prefix(&self) -> &str {
+            "*"
         }
-    }
 
-    fn handle_flip_flop(sequence: &mut LinkedList<(String, String, Pulse)>, pulse: Pulse, state: &mut bool, outputs: &Vec<String>, flip_flop_id: &String) {
-        if pulse == High {
-            return;
+        fn receive(&mut self, source: &str, pulse: Pulse) -> Option<Pulse> {
+            self.0.insert(source.to_string(), pulse);
+            Some(if self.0.values().all(|&p| p == High) { High } else { Low })
         }
 
-        let pulse_to_send = if *state {
-            Low
-        } else {
-            High
-        };
+        fn connect(&mut self, input: &str) {
+            self.0.insert(input.to_string(), Low);
+        }
+    }
 
-        *state = !*state;
+    #[test]
+    fn it_parses_new_gates() {
+        let input: &str = indoc! {"
+        broadcaster -> a, b
+        %a -> and
+        %b -> and
+        *and -> out
+        @out"};
 
-        for o in outputs {
-            sequence.push_back((flip_flop_id.clone(), o.clone(), pulse_to_send))
-        }
+        let gates = |prefix: char, text: &str| -> Option<(String, Box<dyn Gate>)> {
+            match prefix {
+                '*' => Some((text.to_string(), Box::new(And::default()))),
+                _ => built_in_gate(prefix, text)
+            }
+        };
+        let mut network = Network::new(parse_configuration_with(input, gates).unwrap());
+        let pulses: Vec<_> = network.press().into_iter()
+            .filter(|event| event.source == "and")
+            .map(|event| event.pulse)
+            .collect();
+        assert_eq!(vec![Low, High], pulses);
+        assert_eq!(Err(ParseError::new(20, 4, 1, "unexpected module type `*`")), parse_configuration(input));
     }
 
-    fn handle_broadcaster(sequence: &mut LinkedList<(String, String, Pulse)>, outputs: &Vec<String>, pulse: Pulse) {
-        for o in outputs {
-            sequence.push_back(("broadcaster".to_string(), o.clone(), pulse))
-        }
+    #[test]
+    fn it_reports_an_unexpected_module() {
+        let input: &str = indoc! {"
+        broadcaster -> a
+        %a -> inv, con
+        ?inv -> b"};
+
+        assert_eq!(Err(ParseError::new(20, 3, 1, "unexpected module type `?`")), parse_configuration(input));
+        assert_eq!(Err(ParseError::new(20, 1, 1, "unexpected module type `#`")), parse_configuration("#cnt -> a"));
+        assert_eq!(Err(SolveError::unsolvable(20, "`rx` is not fed by a single conjunction")), steps_to_activate_rx("broadcaster -> a\n%a -> rx"));
+        assert_eq!(
+            Err(SolveError::unsolvable(20, "conjunction `c` feeding `rx` has no inputs")),
+            steps_to_activate_rx("broadcaster -> a\n%a -> b\n&c -> rx")
+        );
     }
-}
\ No newline at end of file
+}
@@ -1,72 +1,107 @@
-use std::collections::{HashSet, LinkedList};
-
 use num::Integer;
 
-use crate::input_reader::read_lines;
+use crate::grid::{Grid, Point};
+use crate::parse_error::ParseError;
+use crate::search::reachable;
+use crate::solution::{Answer, Solution, SolveError};
 
-struct Grid {
-    rocks: HashSet<(isize, isize)>,
-    start: (isize, isize),
-    length: isize,
-    width: isize,
+// the garden plots `.`, the rocks `#` and the starting plot `S`
+pub struct Garden {
+    tiles: Grid<char>,
+    pub start: Point,
 }
 
-impl Grid {
-    fn build_from(input: &str) -> Self {
-        let rows = read_lines(input);
-        let length = rows.len() as isize;
-        let width = rows[0].len() as isize;
-        let mut rocks: HashSet<(isize, isize)> = HashSet::new();
-        let mut starting: Option<(isize, isize)> = None;
-        for (y, row) in rows.iter().enumerate() {
-            for (x, c) in row.chars().enumerate() {
-                match c {
-                    '#' => { rocks.insert((x as isize, y as isize)); }
-                    'S' => { starting = Some((x as isize, y as isize)) }
-                    _ => {}
-                }
-            }
-        }
+impl Garden {
+    pub fn build_from(input: &str) -> Result<Self, ParseError> {
+        let tiles = Grid::parse(21, input, ".#S")?;
+        let start = tiles.position(|&c| c == 'S')
+            .ok_or_else(|| ParseError::end_of_input(21, tiles.height() + 1, "a starting position `S`"))?;
+        Ok(Garden { tiles, start })
+    }
+
+    pub fn fill(&self, start: Point, steps: usize) -> usize {
+        self.reachable_plots(start, steps, |p| self.tiles.get(p).is_some_and(|&tile| tile != '#'))
+    }
+
+    // walks on the garden repeated infinitely in every direction, plot by plot
+    pub fn fill_infinite(&self, steps: usize) -> usize {
+        let garden = self.tiles.wrapping();
+        self.reachable_plots(self.start, steps, |p| *garden.get(p) != '#')
+    }
 
-        Grid { rocks, start: starting.unwrap(), length, width }
+    fn reachable_plots(&self, start: Point, steps: usize, is_plot: impl Fn(Point) -> bool) -> usize {
+        // a plot reached early can be reached again at the end by walking back and forth
+        reachable([start], |position| position.neighbours().into_iter().filter(|&p| is_plot(p)), steps)
+            .values()
+            .filter(|&&distance| (steps - distance).is_even())
+            .count()
     }
 
-    fn fill(&self, start: (isize, isize), steps: usize) -> usize {
-        let mut garden_plots: HashSet<(isize, isize)> = HashSet::new();
-        let mut seen: HashSet<(isize, isize)> = HashSet::new();
-        seen.insert(start);
+    // the garden is repeated infinitely in every direction
+    pub fn count_garden_plots_in_infinite_grid(&self, steps: isize) -> Result<usize, SolveError> {
+        // grid is a square
+        let (width, height) = (self.tiles.width() as isize, self.tiles.height() as isize);
+        if width != height {
+            return Err(SolveError::unsolvable(21, format!("the garden is {}x{}, it must be a square", width, height)));
+        }
+        let size = width;
 
-        let mut queue: LinkedList<((isize, isize), usize)> = LinkedList::new();
-        queue.push_back((start, steps));
+        // star is in the middle of the grid
+        let start = self.start;
+        if start.x != size / 2 || start.y != size / 2 {
+            return Err(SolveError::unsolvable(21, format!("the start is at ({}, {}), it must be in the middle at ({}, {})", start.x, start.y, size / 2, size / 2)));
+        }
 
-        while let Some(((x, y), remaining_steps)) = queue.pop_front() {
-            if remaining_steps.is_even() {
-                garden_plots.insert((x, y));
-            }
+        // best case we can arrive at the middle of a grid
+        if steps < size || steps % size != size / 2 {
+            return Err(SolveError::unsolvable(21, format!("{} steps do not end in the middle of a garden beyond the first one", steps)));
+        }
 
-            if remaining_steps == 0 {
-                continue;
-            }
+        let grid_width = (steps / size - 1) as usize;
 
-            for p in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
-                if p.0 < 0 || p.1 < 0 || p.0 >= self.width || p.1 >= self.length {
-                    continue;
-                }
+        let odd = (grid_width / 2 * 2 + 1).pow(2);
+        let even = (grid_width.div_ceil(2) * 2).pow(2);
 
-                if self.rocks.contains(&p) {
-                    continue;
-                }
+        let odd_points = self.fill(start, (size + 2) as usize);
+        let even_points = self.fill(start, (size + 1) as usize);
 
-                if seen.contains(&p) {
-                    continue;
-                }
+        let corner_t = self.fill(Point::new(start.x, size - 1), (size - 1) as usize);
+        let corner_r = self.fill(Point::new(size - 1, start.y), (size - 1) as usize);
+        let corner_l = self.fill(Point::new(start.x, 0), (size - 1) as usize);
+        let corner_b = self.fill(Point::new(0, start.y), (size - 1) as usize);
 
-                seen.insert(p);
-                queue.push_back((p, remaining_steps - 1));
-            }
-        }
+        let small_tr = self.fill(Point::new(size - 1, 0), (size / 2 - 1) as usize);
+        let small_tl = self.fill(Point::new(size - 1, size - 1), (size / 2 - 1) as usize);
+        let small_br = self.fill(Point::new(0, 0), (size / 2 - 1) as usize);
+        let small_bl = self.fill(Point::new(0, size - 1), (size / 2 - 1) as usize);
 
-        garden_plots.len()
+        let large_tr = self.fill(Point::new(size - 1, 0), (size * 3 / 2 - 1) as usize);
+        let large_tl = self.fill(Point::new(size - 1, size - 1), (size * 3 / 2 - 1) as usize);
+        let large_br = self.fill(Point::new(0, 0), (size * 3 / 2 - 1) as usize);
+        let large_bl = self.fill(Point::new(0, size - 1), (size * 3 / 2 - 1) as usize);
+
+        Ok(odd * odd_points +
+            even * even_points +
+            corner_t + corner_r + corner_b + corner_l +
+            (grid_width + 1) * (small_tr + small_tl + small_br + small_bl) +
+            grid_width * (large_tr + large_tl + large_br + large_bl))
+    }
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(Garden::build_from(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        let garden = Garden::build_from(input)?;
+        Ok(garden.fill(garden.start, 64).into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(Garden::build_from(input)?.count_garden_plots_in_infinite_grid(26501365)?.into())
     }
 }
 
@@ -81,61 +116,36 @@ mod tests {
     fn it_solves_first_part() {
         let input = &read_input_file("input_day21.txt");
 
-        assert_eq!(3729, Grid::build_from(input).fill(Grid::build_from(input).start, 64))
+        assert_eq!(3729, Garden::build_from(input).unwrap().fill(Garden::build_from(input).unwrap().start, 64))
     }
 
     #[test]
     fn it_solves_second_part() {
         let input = &read_input_file("input_day21.txt");
 
-        let grid = Grid::build_from(input);
-        // grid is a square
-        assert_eq!(grid.length, grid.width);
-        let size = grid.length;
-
-        // star is in the middle of the grid
-        let start = grid.start;
-        assert_eq!(start.0, start.1);
-        assert_eq!(start.0, size / 2);
-
-        // best case we can arrive at the middle of a grid
-        let steps: isize = 26501365;
-        assert_eq!(steps % size, size / 2);
-
-        let grid_width = (steps / size - 1) as usize;
-
-        let odd = (grid_width / 2 * 2 + 1).pow(2);
-        let even = ((grid_width + 1) / 2 * 2).pow(2);
-
-        let odd_points = grid.fill(start, (size + 2) as usize);
-        let even_points = grid.fill(start, (size + 1) as usize);
-
-        let corner_t = grid.fill((start.0, size - 1), (size - 1) as usize);
-        let corner_r = grid.fill((size - 1, start.1), (size - 1) as usize);
-        let corner_l = grid.fill((start.0, 0), (size - 1) as usize);
-        let corner_b = grid.fill((0, start.1), (size - 1) as usize);
-
-        let small_tr = grid.fill((size - 1, 0), (size / 2 - 1) as usize);
-        let small_tl = grid.fill((size - 1, size - 1), (size / 2 - 1) as usize);
-        let small_br = grid.fill((0, 0), (size / 2 - 1) as usize);
-        let small_bl = grid.fill((0, size - 1), (size / 2 - 1) as usize);
-
-        let large_tr = grid.fill((size - 1, 0), (size * 3 / 2 - 1) as usize);
-        let large_tl = grid.fill((size - 1, size - 1), (size * 3 / 2 - 1) as usize);
-        let large_br = grid.fill((0, 0), (size * 3 / 2 - 1) as usize);
-        let large_bl = grid.fill((0, size - 1), (size * 3 / 2 - 1) as usize);
+        assert_eq!(Ok(621289922886149), Garden::build_from(input).unwrap().count_garden_plots_in_infinite_grid(26501365));
+    }
 
-        let total = odd * odd_points +
-            even * even_points +
-            corner_t + corner_r + corner_b + corner_l +
-            (grid_width + 1) * (small_tr + small_tl + small_br + small_bl) +
-            grid_width * (large_tr + large_tl + large_br + large_bl);
+    #[test]
+    fn it_calculates_the_number_of_garden_plots() {
+        let input = indoc! {"
+        ...........
+        .....###.#.
+        .###.##..#.
+        ..#.#...#..
+        ....#.#....
+        .##..S####.
+        .##..#...#.
+        .......##..
+        .##.#.####.
+        .##..##.##.
+        ..........."};
 
-        assert_eq!(621289922886149, total);
+        assert_eq!(16, Garden::build_from(input).unwrap().fill(Garden::build_from(input).unwrap().start, 6))
     }
 
     #[test]
-    fn it_calculates_the_number_of_garden_plots() {
+    fn it_calculates_the_number_of_garden_plots_in_infinite_grid() {
         let input = indoc! {"
         ...........
         .....###.#.
@@ -149,6 +159,27 @@ mod tests {
         .##..##.##.
         ..........."};
 
-        assert_eq!(16, Grid::build_from(input).fill(Grid::build_from(input).start, 6))
+        let garden = Garden::build_from(input).unwrap();
+        assert_eq!(16, garden.fill_infinite(6));
+        assert_eq!(50, garden.fill_infinite(10));
+        assert_eq!(1594, garden.fill_infinite(50));
+    }
+
+    #[test]
+    fn it_reports_gardens_without_a_shortcut() {
+        let square = Garden::build_from("...\n.S.\n...").unwrap();
+        assert!(square.count_garden_plots_in_infinite_grid(4).is_ok());
+        assert_eq!(
+            Err(SolveError::unsolvable(21, "1 steps do not end in the middle of a garden beyond the first one")),
+            square.count_garden_plots_in_infinite_grid(1)
+        );
+        assert_eq!(
+            Err(SolveError::unsolvable(21, "the garden is 3x2, it must be a square")),
+            Day21.part2("...\n.S#")
+        );
+        assert_eq!(
+            Err(SolveError::unsolvable(21, "the start is at (0, 1), it must be in the middle at (1, 1)")),
+            Day21.part2("...\nS..\n...")
+        );
     }
 }
\ No newline at end of file
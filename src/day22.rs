@@ -1,40 +1,41 @@
 use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
 
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
-struct Position {
-    x: usize,
-    y: usize,
-    z: usize,
+pub struct Position {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
 }
 
 impl Position {
-    fn new(raw_position: &str) -> Self {
-        let coords = raw_position.split(",").map(|c| c.parse::<usize>().unwrap()).collect::<Vec<_>>();
-        Position { x: coords[0], y: coords[1], z: coords[2] }
+    pub fn new<'a>(line: &Line<'a>, raw_position: &'a str) -> Result<Self, ParseError> {
+        let coords = raw_position.split(',').map(|c| line.parse::<usize>(c)).collect::<Result<Vec<_>, _>>()?;
+        if coords.len() != 3 {
+            return Err(line.error(raw_position, format!("expected 3 coordinates, found {}", coords.len())));
+        }
+        Ok(Position { x: coords[0], y: coords[1], z: coords[2] })
     }
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
-struct Brick {
-    start: Position,
-    end: Position,
+pub struct Brick {
+    pub start: Position,
+    pub end: Position,
 }
 
-struct Snapshot {
+pub struct Snapshot {
     bricks: Vec<Brick>,
     supporting_bricks: HashMap<Brick, HashSet<Brick>>,
     supported_bricks: HashMap<Brick, HashSet<Brick>>,
 }
 
 impl Snapshot {
-    fn new(input: &str) -> Self {
-        let mut bricks = read_lines(input).iter().map(|line| {
-            let parts = line.split("~").collect::<Vec<_>>();
-            Brick { start: Position::new(parts[0]), end: Position::new(parts[1]) }
-        }).collect::<Vec<_>>();
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        let mut bricks = parse_lines(22, input, Brick::parse)?;
 
         free_fall(&mut bricks);
 
@@ -58,17 +59,21 @@ impl Snapshot {
             }
 
             for b in &current_supported_bricks {
-                let current_supporting_bricks = supported_bricks.entry((*b).clone()).or_insert(HashSet::new());
+                let current_supporting_bricks = supported_bricks.entry((*b).clone()).or_default();
                 current_supporting_bricks.insert((*current).clone());
             }
 
             supporting_bricks.insert((*current).clone(), current_supported_bricks);
         }
 
-        Snapshot { bricks, supporting_bricks, supported_bricks }
+        Ok(Snapshot { bricks, supporting_bricks, supported_bricks })
+    }
+
+    pub fn bricks(&self) -> &[Brick] {
+        &self.bricks
     }
 
-    fn count_disintegrable_bricks(&self) -> usize {
+    pub fn count_disintegrable_bricks(&self) -> usize {
         let mut count: usize = 0;
 
         for b in self.supporting_bricks.keys() {
@@ -80,7 +85,7 @@ impl Snapshot {
         count
     }
 
-    fn chain_reaction(&self) -> usize {
+    pub fn chain_reaction(&self) -> usize {
         let mut total = 0;
 
         for brick in &self.bricks {
@@ -106,15 +111,13 @@ impl Snapshot {
     }
 }
 
-fn free_fall(bricks: &mut Vec<Brick>) {
-    bricks.sort_by(|b1, b2| b1.start.z.cmp(&b2.start.z));
+fn free_fall(bricks: &mut [Brick]) {
+    bricks.sort_by_key(|b1| b1.start.z);
 
     for current_index in 0..bricks.len() {
         let current = &bricks[current_index];
         let mut z_max = 0;
-        for prev_index in 0..current_index {
-            let prev = &bricks[prev_index];
-
+        for prev in &bricks[..current_index] {
             if current.is_overlapped_by(prev) {
                 z_max = max(z_max, prev.end.z + 1);
             }
@@ -124,11 +127,21 @@ fn free_fall(bricks: &mut Vec<Brick>) {
         current.update_z(z_max);
     }
 
-    bricks.sort_by(|b1, b2| b1.start.z.cmp(&b2.start.z));
+    bricks.sort_by_key(|b1| b1.start.z);
 }
 
 impl Brick {
-    fn is_overlapped_by(&self, other: &Self) -> bool {
+    pub fn parse(line: &Line) -> Result<Self, ParseError> {
+        let (raw_start, raw_end) = line.split_once(line.text, "~")?;
+        let start = Position::new(line, raw_start)?;
+        let end = Position::new(line, raw_end)?;
+        if end.x < start.x || end.y < start.y || end.z < start.z {
+            return Err(line.error(raw_end, format!("expected the end of the brick after its start, found `{}`", line.text)));
+        }
+        Ok(Brick { start, end })
+    }
+
+    pub fn is_overlapped_by(&self, other: &Self) -> bool {
         max(self.start.x, other.start.x) <= min(self.end.x, other.end.x) &&
             max(self.start.y, other.start.y) <= min(self.end.y, other.end.y)
     }
@@ -140,6 +153,22 @@ impl Brick {
     }
 }
 
+pub struct Day22;
+
+impl Solution for Day22 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_lines(22, input, Brick::parse).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(Snapshot::new(input)?.count_disintegrable_bricks().into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(Snapshot::new(input)?.chain_reaction().into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -151,7 +180,7 @@ mod tests {
     fn it_solves_first_part() {
         let input = &read_input_file("input_day22.txt");
 
-        assert_eq!(527, Snapshot::new(input).count_disintegrable_bricks());
+        assert_eq!(527, Snapshot::new(input).unwrap().count_disintegrable_bricks());
     }
 
     #[test]
@@ -159,7 +188,7 @@ mod tests {
     fn it_solves_second_part() {
         let input = &read_input_file("input_day22.txt");
 
-        assert_eq!(100376, Snapshot::new(input).chain_reaction());
+        assert_eq!(100376, Snapshot::new(input).unwrap().chain_reaction());
     }
 
     #[test]
@@ -173,7 +202,7 @@ mod tests {
             0,1,6~2,1,6
             1,1,8~1,1,9"};
 
-        assert_eq!(5, Snapshot::new(input).count_disintegrable_bricks());
+        assert_eq!(5, Snapshot::new(input).unwrap().count_disintegrable_bricks());
     }
 
     #[test]
@@ -187,6 +216,12 @@ mod tests {
             0,1,6~2,1,6
             1,1,8~1,1,9"};
 
-        assert_eq!(7, Snapshot::new(input).chain_reaction());
+        assert_eq!(7, Snapshot::new(input).unwrap().chain_reaction());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_reports_a_malformed_brick() {
+        assert_eq!(Err(ParseError::new(22, 2, 7, "expected 3 coordinates, found 2")), Snapshot::new("1,0,1~1,2,1\n0,0,2~2,0").map(|s| s.bricks().len()));
+        assert_eq!(Err(ParseError::new(22, 1, 7, "expected the end of the brick after its start, found `1,0,2~1,2,1`")), Brick::parse(&Line::new(22, 1, "1,0,2~1,2,1")));
+    }
+}
@@ -1,75 +1,67 @@
-use std::collections::{HashMap, HashSet};
-use crate::input_reader::read_lines;
-
-struct Graph {
-    map: Vec<Vec<char>>,
-    width: usize,
-    length: usize,
-    start: (usize, usize),
-    end: (usize, usize),
+use std::collections::{HashMap, HashSet, LinkedList};
+
+use crate::grid::{Direction, Grid, Point};
+use crate::grid::Direction::*;
+use crate::parse_error::{Line, ParseError};
+use crate::search::longest_path;
+use crate::solution::{Answer, Solution, SolveError};
+
+pub struct Graph {
+    map: Grid<char>,
+    pub start: Point,
+    pub end: Point,
 }
 
 impl Graph {
-    fn new(input: &str) -> Self {
-        let map = read_lines(input).iter()
-            .map(|l| l.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-        let width = map[0].len();
-        let length = map.len();
-
-        Self { map, width, length, start: (1, 0), end: (width - 2, length - 1) }
+    pub fn new(input: &str) -> Result<Self, ParseError> {
+        let map = parse_map(input)?;
+        let (start, end) = start_and_end(&map);
+
+        Ok(Self { map, start, end })
+    }
+
+    fn is_open(&self, point: Point) -> bool {
+        self.map.get(point).is_some_and(|&tile| tile != '#')
     }
 
-    fn find_point_of_interest(&self) -> Vec<(usize, usize)> {
+    pub fn find_point_of_interest(&self) -> Vec<Point> {
         let mut pois = vec![];
         pois.push(self.start);
         pois.push(self.end);
 
-        for (y, row) in self.map.iter().enumerate() {
-            for (x, tile) in row.iter().enumerate() {
-                if *tile == '#' {
-                    continue;
-                }
-
-                let mut neigbours = 0;
-                for n in [(x as isize - 1, y as isize), ((x + 1) as isize, y as isize), (x as isize, y as isize - 1), (x as isize, (y + 1) as isize)] {
-                    if self.is_in_range(n) && self.map[n.1 as usize][n.0 as usize] != '#' {
-                        neigbours += 1;
-                    }
-                }
+        for point in self.map.points() {
+            if !self.is_open(point) {
+                continue;
+            }
 
-                if neigbours >= 3 {
-                    pois.push((x, y))
-                }
+            if self.map.neighbours(point).filter(|&n| self.is_open(n)).count() >= 3 {
+                pois.push(point)
             }
         }
 
         pois
     }
 
-    fn reduced_graph(&self) -> HashMap<(usize, usize), HashMap<(usize, usize), usize>> {
+    pub fn reduced_graph(&self) -> HashMap<Point, HashMap<Point, usize>> {
         let mut reduced_graph = HashMap::new();
 
         let pois = self.find_point_of_interest();
-        for (start_x, start_y) in &pois {
-            let mut stack = vec![(0, *start_x, *start_y)];
+        for &start in &pois {
+            let mut stack = vec![(0, start)];
             let mut seen = HashSet::new();
-            seen.insert((*start_x, *start_y));
-
-            while !stack.is_empty() {
-                let (steps, x, y) = stack.pop().unwrap();
+            seen.insert(start);
 
-                if pois.contains(&(x, y)) && (x, y) != (*start_x, *start_y) {
-                    let e = reduced_graph.entry((*start_x, *start_y)).or_insert(HashMap::new());
-                    e.insert((x, y), steps);
+            while let Some((steps, point)) = stack.pop() {
+                if pois.contains(&point) && point != start {
+                    let e = reduced_graph.entry(start).or_insert(HashMap::new());
+                    e.insert(point, steps);
                     continue;
                 }
 
-                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-                    let n = ((x as isize + dx), (y as isize + dy));
-                    if self.is_in_range(n) && self.map[n.1 as usize][n.0 as usize] != '#' && !seen.contains(&(n.0 as usize, n.1 as usize)) {
-                        stack.push((steps + 1, n.0 as usize, n.1 as usize));
-                        seen.insert((n.0 as usize, n.1 as usize));
+                for n in self.map.neighbours(point) {
+                    if self.is_open(n) && !seen.contains(&n) {
+                        stack.push((steps + 1, n));
+                        seen.insert(n);
                     }
                 }
             }
@@ -77,25 +69,132 @@ impl Graph {
 
         reduced_graph
     }
+}
+
+// the hike starts from the second tile of the first row and ends on the second last tile of the last row
+fn start_and_end(map: &Grid<char>) -> (Point, Point) {
+    (Point::new(1, 0), Point::from((map.width() - 2, map.height() - 1)))
+}
+
+fn parse_map(input: &str) -> Result<Grid<char>, ParseError> {
+    let map = Grid::parse(23, input, "#.<>^v")?;
+    let rows: Vec<_> = input.lines().collect();
+    let width = map.width();
+
+    if width < 3 || map[Point::new(1, 0)] != '.' {
+        return Err(Line::new(23, 1, rows[0]).error(rows[0].get(1..).unwrap_or(rows[0]), "expected the start on the second tile of the first row"));
+    }
+
+    let last = rows[map.height() - 1];
+    if map[start_and_end(&map).1] != '.' {
+        return Err(Line::new(23, map.height(), last).error(&last[width - 2..], "expected the end on the second last tile of the last row"));
+    }
+
+    Ok(map)
+}
+
+type Walk = (Point, HashSet<Point>);
+
+// `None` when no hike reaches the end
+pub fn find_max_path_length_without_slopes(input: &str) -> Result<Option<usize>, ParseError> {
+    let graph = Graph::new(input)?;
+    let reduced_graph = graph.reduced_graph();
+    let hike = longest_path(
+        graph.start,
+        |node| reduced_graph.get(node).into_iter().flatten().map(|(&n, &length)| (n, length)),
+        |&node| node == graph.end,
+    );
 
-    fn is_in_range(&self, (x, y): (isize, isize)) -> bool {
-        0 <= x && x < self.width as isize && 0 <= y && y < self.length as isize
+    Ok(hike.map(|hike| hike.cost))
+}
+
+pub fn find_path_lengths(input: &str) -> Result<Vec<usize>, ParseError> {
+    let map = parse_map(input)?;
+    let (start, target) = start_and_end(&map);
+
+    let mut queue: LinkedList<Walk> = LinkedList::new();
+    queue.push_front((start, HashSet::new()));
+    let mut path_lengths: Vec<usize> = vec![];
+
+    while let Some((node, path)) = queue.pop_front() {
+        if node == target {
+            path_lengths.push(path.len());
+            continue;
+        }
+
+        for direction in [Right, Left, Down, Up] {
+            expand(node, direction, &map, &path, &mut queue);
+        }
     }
+
+    path_lengths.sort();
+    Ok(path_lengths)
+}
+
+fn expand(
+    node: Point,
+    direction: Direction,
+    map: &Grid<char>,
+    path: &HashSet<Point>,
+    queue: &mut LinkedList<Walk>,
+) {
+    let neighbour = node.step(direction);
+    let Some(&tile) = map.get(neighbour) else { return };
+
+    if path.contains(&neighbour) {
+        return;
+    }
+
+    if tile == '.' {
+        let mut new_path = path.clone();
+        new_path.insert(neighbour);
+        queue.push_front((neighbour, new_path));
+        return;
+    }
+
+    // slopes are drawn as the arrow of the direction they can be walked in
+    if tile == direction.arrow() {
+        let mut new_path = path.clone();
+        new_path.insert(neighbour);
+        let neighbour = neighbour.step(direction);
+        new_path.insert(neighbour);
+        queue.push_front((neighbour, new_path));
+    }
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(Graph::new(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        let longest = find_path_lengths(input)?.last().copied().ok_or_else(no_hike)?;
+        Ok(longest.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(find_max_path_length_without_slopes(input)?.ok_or_else(no_hike)?.into())
+    }
+}
+
+fn no_hike() -> SolveError {
+    SolveError::unsolvable(23, "no hike reaches the end")
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashSet, LinkedList};
     use indoc::indoc;
-    use itertools::max;
-    use crate::day23::Graph;
-    use crate::input_reader::{read_input_file, read_lines};
+
+    use crate::day23::*;
+    use crate::input_reader::read_input_file;
 
     #[test]
     fn it_solves_first_part() {
         let input = &read_input_file("input_day23.txt");
 
-        let path_lengths = find_path_lengths(input);
+        let path_lengths = find_path_lengths(input).unwrap();
         assert_eq!(2238, *path_lengths.last().unwrap());
     }
 
@@ -104,7 +203,7 @@ mod tests {
     fn it_solves_second_part() {
         let input = &read_input_file("input_day23.txt");
 
-        assert_eq!(6398, find_max_path_length_without_slopes(input));
+        assert_eq!(Ok(Some(6398)), find_max_path_length_without_slopes(input));
     }
 
     #[test]
@@ -134,137 +233,25 @@ mod tests {
             #.....###...###...#...#
             #####################.#"};
 
-        let path_lengths = find_path_lengths(input);
+        let path_lengths = find_path_lengths(input).unwrap();
         assert_eq!(vec![74, 82, 82, 86, 90, 94], path_lengths);
         assert_eq!(94, *path_lengths.last().unwrap());
-        assert_eq!(154, find_max_path_length_without_slopes(input));
+        assert_eq!(Ok(Some(154)), find_max_path_length_without_slopes(input));
     }
 
-    fn find_max_path_length_without_slopes(input: &str) -> usize {
-        let graph = Graph::new(input);
-        let reduced_graph = graph.reduced_graph();
-        let mut queue: LinkedList<((usize, usize), Vec<(usize, usize)>, usize)> = LinkedList::new();
-        queue.push_front((graph.start, vec![], 0));
-        let mut path_lengths: Vec<usize> = vec![];
-
-        while let Some((node, path, current_length)) = queue.pop_front() {
-            if node == graph.end {
-                path_lengths.push(current_length);
-                continue;
-            }
-
-            for (n, &length) in &reduced_graph[&node] {
-                if path.contains(n) {
-                    continue
-                }
-
-                let mut new_path = path.clone();
-                new_path.push(n.clone());
-                queue.push_front((n.clone(), new_path, current_length + length));
-            }
-        }
-
-        max(path_lengths).unwrap()
-    }
-
-    fn find_path_lengths(input: &str) -> Vec<usize> {
-        let map = read_lines(input).iter()
-            .map(|l| l.chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-        let width = map[0].len();
-        let length = map.len();
-        let dimensions = (width, length);
-        let target = (width - 2, length - 1);
-        let start: (usize, usize) = (1, 0);
-
-        let mut queue: LinkedList<((usize, usize), HashSet<(usize, usize)>)> = LinkedList::new();
-        queue.push_front((start, HashSet::new()));
-        let mut path_lengths: Vec<usize> = vec![];
-
-        while let Some((node, path)) = queue.pop_front() {
-            if node == target {
-                path_lengths.push(path.len());
-                continue;
-            }
-            let (x, y) = node;
-            expand(
-                ((x + 1) as isize, y as isize),
-                '>',
-                &map,
-                &dimensions,
-                &path,
-                &mut queue,
-            );
-
-            expand(
-                ((x as isize) - 1, y as isize),
-                '<',
-                &map,
-                &dimensions,
-                &path,
-                &mut queue,
-            );
-
-            expand(
-                (x as isize, (y + 1) as isize),
-                'v',
-                &map,
-                &dimensions,
-                &path,
-                &mut queue,
-            );
-
-            expand(
-                (x as isize, (y as isize) - 1),
-                '^',
-                &map,
-                &dimensions,
-                &path,
-                &mut queue,
-            );
-        }
-
-        path_lengths.sort();
-        path_lengths
-    }
-
-    fn expand(
-        neighbour: (isize, isize),
-        slope: char,
-        map: &Vec<Vec<char>>,
-        (width, length): &(usize, usize),
-        path: &HashSet<(usize, usize)>,
-        queue: &mut LinkedList<((usize, usize), HashSet<(usize, usize)>)>,
-    ) {
-        if neighbour.0 < 0 || neighbour.0 >= (*width as isize) || neighbour.1 < 0 || neighbour.1 >= (*length as isize) {
-            return;
-        }
-
-        let neighbour = (neighbour.0 as usize, neighbour.1 as usize);
-        if path.contains(&neighbour) {
-            return;
-        }
+    #[test]
+    fn it_reports_a_blocked_end() {
+        let input = indoc! {"
+            #.###
+            #...#
+            ###.#"};
 
-        let tile = map[neighbour.1][neighbour.0];
-        if tile == '.' {
-            let mut new_path = path.clone();
-            new_path.insert(neighbour);
-            queue.push_front((neighbour, new_path));
-            return;
-        }
+        assert_eq!(Ok(vec![4]), find_path_lengths(input));
+        assert_eq!(Err(ParseError::new(23, 3, 4, "expected the end on the second last tile of the last row")), find_path_lengths("#.###\n#...#\n####."));
 
-        if tile == slope {
-            let mut new_path = path.clone();
-            new_path.insert(neighbour);
-            let neighbour = match slope {
-                '^' => (neighbour.0, neighbour.1 - 1),
-                'v' => (neighbour.0, neighbour.1 + 1),
-                '>' => (neighbour.0 + 1, neighbour.1),
-                '<' => (neighbour.0 - 1, neighbour.1),
-                _ => panic!("unexpected slope")
-            };
-            new_path.insert(neighbour);
-            queue.push_front((neighbour, new_path));
-        }
+        let walled = "#.#\n###\n#.#";
+        assert_eq!(Ok(None), find_max_path_length_without_slopes(walled));
+        assert_eq!(Err(SolveError::unsolvable(23, "no hike reaches the end")), Day23.part1(walled));
+        assert_eq!(Err(SolveError::unsolvable(23, "no hike reaches the end")), Day23.part2(walled));
     }
-}
\ No newline at end of file
+}
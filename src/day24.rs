@@ -1,22 +1,23 @@
 use itertools::Itertools;
 use num::Zero;
 
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Clone, Debug)]
-struct Point(f64, f64, f64);
+pub struct Point(pub f64, pub f64, pub f64);
 
 #[derive(Clone, Debug)]
-struct Hail {
-    start: Point,
-    end: Point,
+pub struct Hail {
+    pub start: Point,
+    pub end: Point,
 }
 
 impl Hail {
     fn linear_equation_xy(&self) -> (f64, f64, f64) {
         let a = self.end.1 - self.start.1;
         let b = self.start.0 - self.end.0;
-        let c = -1.0 * self.start.0 * (self.end.1 - self.start.1) + self.start.1 * (self.end.0 - self.start.0);
+        let c = -self.start.0 * (self.end.1 - self.start.1) + self.start.1 * (self.end.0 - self.start.0);
         (a, b, c)
     }
 }
@@ -35,27 +36,33 @@ fn calculate_distance_xy((x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> f64 {
     ((x1 - x2) * (x1 - x2) + (y1 - y2) * (y1 - y2)).sqrt()
 }
 
-fn parse_hails(input: &str) -> Vec<Hail> {
-    read_lines(input).iter().map(|l| {
-        let parts = l.split(" @ ").collect::<Vec<_>>();
-        let initial_positions = parts[0].split(", ")
-            .map(|v| v.parse::<f64>().unwrap())
-            .collect::<Vec<_>>();
-        let speeds = parts[1].split(", ")
-            .map(|v| v.trim().parse::<f64>().unwrap())
-            .collect::<Vec<_>>();
-        Hail {
+pub fn parse_hails(input: &str) -> Result<Vec<Hail>, ParseError> {
+    parse_lines(24, input, |l| {
+        let (raw_positions, raw_speeds) = l.split_once(l.text, " @ ")?;
+        let initial_positions = parse_coordinates(l, raw_positions)?;
+        let speeds = parse_coordinates(l, raw_speeds)?;
+        Ok(Hail {
             start: Point(initial_positions[0],
                          initial_positions[1],
                          initial_positions[2]),
             end: Point(initial_positions[0] + speeds[0],
                        initial_positions[1] + speeds[1],
                        initial_positions[2] + speeds[2]),
-        }
-    }).collect::<Vec<_>>()
+        })
+    })
 }
 
-fn count_intersecting_hails(hails: Vec<Hail>, test_area: (f64, f64)) -> usize {
+fn parse_coordinates<'a>(line: &Line<'a>, raw_coordinates: &'a str) -> Result<Vec<f64>, ParseError> {
+    let coordinates = raw_coordinates.split(", ")
+        .map(|v| line.parse::<f64>(v))
+        .collect::<Result<Vec<_>, _>>()?;
+    if coordinates.len() != 3 {
+        return Err(line.error(raw_coordinates, format!("expected 3 coordinates, found {}", coordinates.len())));
+    }
+    Ok(coordinates)
+}
+
+pub fn count_intersecting_hails(hails: Vec<Hail>, test_area: (f64, f64)) -> usize {
     let mut count: usize = 0;
 
     for combinations in hails.into_iter().combinations(2) {
@@ -80,7 +87,24 @@ fn are_hails_intersecting(hail: &Hail, other: &Hail, test_area: (f64, f64)) -> b
             d_1_in > d_1_f && d_2_in > d_2_f;
     }
 
-    return false;
+    false
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_hails(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(count_intersecting_hails(parse_hails(input)?, (200000000000000.0, 400000000000000.0)).into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        parse_hails(input)?;
+        Err(SolveError::unsolvable(24, "part 2 has no solver yet"))
+    }
 }
 
 #[cfg(test)]
@@ -96,10 +120,20 @@ mod tests {
     fn it_solves_first_part() {
         let input = &read_input_file("input_day24.txt");
 
-        assert_eq!(12740, count_intersecting_hails(parse_hails(input), (200000000000000.0, 400000000000000.0)));
+        assert_eq!(12740, count_intersecting_hails(parse_hails(input).unwrap(), (200000000000000.0, 400000000000000.0)));
         // part 2 right answer is 741991571910536
     }
 
+    #[test]
+    fn it_reports_a_malformed_hail() {
+        let input = indoc! {"
+        19, 13, 30 @ -2,  1, -2
+        18, 19 @ -1, -1, -2"};
+
+        assert_eq!(Err(ParseError::new(24, 2, 1, "expected 3 coordinates, found 2")), parse_hails(input).map(|hails| hails.len()));
+        assert_eq!(Err(SolveError::unsolvable(24, "part 2 has no solver yet")), Day24.part2(&input.replace("18, 19 @", "18, 19, 22 @")));
+    }
+
     #[test]
     fn it_counts_intersecting_hails() {
         let input = indoc! {"
@@ -109,7 +143,7 @@ mod tests {
         12, 31, 28 @ -1, -2, -1
         20, 19, 15 @  1, -5, -3"};
 
-        assert_eq!(2, count_intersecting_hails(parse_hails(input), (7.0, 27.0)));
+        assert_eq!(2, count_intersecting_hails(parse_hails(input).unwrap(), (7.0, 27.0)));
 
         // let cfg = Config::new();
         // let ctx = Context::new(&cfg);
@@ -1,16 +1,79 @@
+use graphrs::{algorithms::community::louvain, Edge, Graph, GraphSpecs};
+use itertools::Itertools;
+
+use crate::parse_error::{lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
+
+pub fn find_groups(input: &str) -> Result<(Vec<String>, Vec<String>), ParseError> {
+    let mut edges: Vec<Edge<String, _>> = vec![];
+
+    for line in lines(25, input) {
+        let (node, connections) = line.split_once(line.text, ": ")?;
+        for n in connections.split(' ') {
+            if n.is_empty() {
+                return Err(line.error(n, "expected a component name"));
+            }
+            edges.push(Edge::new(node.to_string(), n.to_string()));
+            edges.push(Edge::new(n.to_string(), node.to_string()));
+        }
+    }
+
+    if edges.is_empty() {
+        return Err(ParseError::end_of_input(25, 1, "a connection"));
+    }
+
+    let graph: Graph<String, ()> =
+        Graph::new_from_nodes_and_edges(vec![], edges, GraphSpecs::directed_create_missing())
+            .unwrap();
+    let partitions = louvain::louvain_partitions(&graph, false, Some(0f64), Some(4f64), None).unwrap();
+    let best_partition = partitions.last().unwrap();
+
+    let group1: Vec<String> = best_partition
+        .first()
+        .unwrap()
+        .iter()
+        .cloned()
+        .sorted()
+        .collect();
+
+    let group2: Vec<String> = best_partition
+        .last()
+        .unwrap()
+        .iter()
+        .cloned()
+        .sorted()
+        .collect();
+    Ok((group1, group2))
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        let (group1, group2) = find_groups(input)?;
+        Ok((group1.len() * group2.len()).into())
+    }
+
+    // there is no second puzzle on the last day
+    fn part2(&self, _input: &str) -> Result<Answer, SolveError> {
+        Ok("Merry Christmas!".into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::cmp::{max, min};
+
     use indoc::indoc;
-    use crate::input_reader::{read_input_file, read_lines};
-    use graphrs::{algorithms::community::louvain, Edge, Graph, GraphSpecs};
-    use itertools::Itertools;
+
+    use crate::day25::*;
+    use crate::input_reader::read_input_file;
 
     #[test]
     fn it_solves_first_part() {
         let input = &read_input_file("input_day25.txt");
 
-        let (group1, group2) = find_groups(input);
+        let (group1, group2) = find_groups(input).unwrap();
         assert_eq!(580800, min(group1.len(), group2.len()) *  max(group1.len(), group2.len()));
     }
 
@@ -32,44 +95,8 @@ mod tests {
             frs: qnr lhk lsr
         "};
 
-        let (group1, group2) = find_groups(input);
+        let (group1, group2) = find_groups(input).unwrap();
         assert_eq!(6, min(group1.len(), group2.len()));
         assert_eq!(9, max(group1.len(), group2.len()));
     }
-
-    fn find_groups(input: &str) -> (Vec<String>, Vec<String>) {
-        let mut edges: Vec<Edge<String, _>> = vec![];
-
-        for line in read_lines(input) {
-            let parts = line.split(": ").collect::<Vec<_>>();
-            let node = parts[0];
-            parts[1].split(" ").for_each(|n| {
-                edges.push(Edge::new(node.to_string(), n.to_string()));
-                edges.push(Edge::new(n.to_string(), node.to_string()));
-            });
-        }
-
-        let graph: Graph<String, ()> =
-            Graph::new_from_nodes_and_edges(vec![], edges, GraphSpecs::directed_create_missing())
-                .unwrap();
-        let partitions = louvain::louvain_partitions(&graph, false, Some(0f64), Some(4f64), None).unwrap();
-        let best_partition = partitions.last().unwrap();
-
-        let group1: Vec<String> = best_partition
-            .first()
-            .unwrap()
-            .iter()
-            .cloned()
-            .sorted()
-            .collect();
-
-        let group2: Vec<String> = best_partition
-            .last()
-            .unwrap()
-            .iter()
-            .cloned()
-            .sorted()
-            .collect();
-        (group1, group2)
-    }
-}
\ No newline at end of file
+}
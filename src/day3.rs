@@ -2,75 +2,65 @@ use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
 
-use crate::input_reader::read_lines;
+use crate::grid::{Grid, Point};
+use crate::parse_error::{lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
-struct EngineSchematic {
-    height: usize,
-    width: usize,
+pub struct EngineSchematic {
     numbers: Vec<Number>,
-    schema: Vec<String>,
+    schema: Grid<char>,
 }
 
 impl EngineSchematic {
-    fn build_form(input: &str) -> EngineSchematic {
-        let schema = read_lines(input);
-
-        let height = schema.len();
-        let width = schema.first().unwrap().len();
+    pub fn build_form(input: &str) -> Result<EngineSchematic, ParseError> {
+        let width = input.lines().next()
+            .ok_or_else(|| ParseError::end_of_input(3, 1, "a schematic"))?
+            .len();
+        if let Some(line) = lines(3, input).find(|line| line.text.len() != width) {
+            return Err(line.error(line.text, format!("expected a row of {} cells, found {}", width, line.text.len())));
+        }
 
-        let numbers: Vec<_> = schema.iter()
+        let numbers: Vec<_> = input.lines()
             .enumerate()
             .flat_map(|(row_index, line)| find_numbers_in_line(row_index, line))
             .collect();
+        let schema = Grid::new(input.lines().map(|line| line.chars().collect()).collect());
 
-        EngineSchematic { height, width, numbers, schema }
+        Ok(EngineSchematic { numbers, schema })
     }
 
-    fn find_numbers_close_to_symbols(&self) -> Vec<&Number> {
+    fn cell_at(&self, (x, y): (usize, usize)) -> Option<char> {
+        self.schema.get(Point::from((x, y))).copied()
+    }
+
+    pub fn find_numbers_close_to_symbols(&self) -> Vec<&Number> {
         self.numbers.iter()
-            .filter_map(|number| {
-                if self.has_a_symbol_neighbour(number) {
-                    Some(number)
-                } else {
-                    None
-                }
-            })
+            .filter(|number| self.has_a_symbol_neighbour(number))
             .collect()
     }
 
     fn has_a_symbol_neighbour(&self, number: &Number) -> bool {
-        number.neighbours_positions().iter()
-            .filter(|(x, y)| *x < self.width && *y < self.height)
-            .any(|(x, y)| -> bool  {
-                let neighbour = self.schema[*y].chars().nth(*x).unwrap();
-                neighbour != '.' && !neighbour.is_ascii_digit()
-            })
+        number.neighbours_positions().into_iter()
+            .filter_map(|position| self.cell_at(position))
+            .any(|neighbour| neighbour != '.' && !neighbour.is_ascii_digit())
     }
 
-    fn sum_numbers_close_to_symbols(&self) -> usize {
+    pub fn sum_numbers_close_to_symbols(&self) -> usize {
         self.find_numbers_close_to_symbols().iter()
             .map(|n| n.value)
             .sum()
     }
 
-    fn find_multiplier_operations(&self) -> HashMap<(usize, usize), Vec<Number>> {
+    pub fn find_multiplier_operations(&self) -> HashMap<(usize, usize), Vec<Number>> {
         let mut result = HashMap::new();
 
         for number in &self.numbers {
             number
                 .neighbours_positions()
-                .iter()
-                .filter(|(x, y)| *x < self.width && *y < self.height)
-                .filter_map(|(x, y)| {
-                    let value = self.schema.get(*y)?.chars().nth(*x)?;
-                    if value == '*' {
-                        Some(((*x, *y), value))
-                    } else {
-                        None
-                    }
-                })
-                .for_each(|(position, _)| {
-                    result.entry(position.clone())
+                .into_iter()
+                .filter(|&position| self.cell_at(position) == Some('*'))
+                .for_each(|position| {
+                    result.entry(position)
                         .or_insert(Vec::new())
                         .push(number.clone());
                 });
@@ -81,11 +71,11 @@ impl EngineSchematic {
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
-struct Number {
-    row_index: usize,
-    value: usize,
-    start: usize,
-    end: usize,
+pub struct Number {
+    pub row_index: usize,
+    pub value: usize,
+    pub start: usize,
+    pub end: usize,
 }
 
 impl Display for Number {
@@ -101,7 +91,7 @@ impl Number {
         Number { row_index, start, end, value }
     }
 
-    fn neighbours_positions(&self) -> Vec<(usize, usize)> {
+    pub fn neighbours_positions(&self) -> Vec<(usize, usize)> {
         let mut neighbours_positions = vec![];
 
         let mut start = self.start;
@@ -122,7 +112,7 @@ impl Number {
     }
 }
 
-fn find_numbers_in_line(row_index: usize, row: &str) -> Vec<Number> {
+pub fn find_numbers_in_line(row_index: usize, row: &str) -> Vec<Number> {
     let mut numbers_in_line = vec![];
     let mut current_index = 0;
     let chars: Vec<char> = row.chars().collect();
@@ -144,21 +134,38 @@ fn find_numbers_in_line(row_index: usize, row: &str) -> Vec<Number> {
     numbers_in_line
 }
 
-fn sum_numbers_close_to_symbols(input: &str) -> usize {
-    EngineSchematic::build_form(input).sum_numbers_close_to_symbols()
+pub fn sum_numbers_close_to_symbols(input: &str) -> Result<usize, ParseError> {
+    Ok(EngineSchematic::build_form(input)?.sum_numbers_close_to_symbols())
 }
 
-fn sum_gear_ratios(input: &str) -> usize {
-    EngineSchematic::build_form(input)
-        .find_multiplier_operations().iter()
-        .filter_map(|(_, numbers)| {
+pub fn sum_gear_ratios(input: &str) -> Result<usize, ParseError> {
+    let sum = EngineSchematic::build_form(input)?
+        .find_multiplier_operations().values()
+        .filter_map(|numbers| {
             if numbers.len() == 2 {
-                Some(numbers.iter().map(|n| n.value).fold(1, |acc, x| acc * x))
+                Some(numbers.iter().map(|n| n.value).product::<usize>())
             } else {
                 None
             }
         })
-        .sum()
+        .sum();
+    Ok(sum)
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(EngineSchematic::build_form(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(sum_numbers_close_to_symbols(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(sum_gear_ratios(input)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -172,14 +179,14 @@ mod tests {
     fn it_solves_first_part() {
         let input = read_input_file("input_day03.txt");
 
-        assert_eq!(560670, sum_numbers_close_to_symbols(&input));
+        assert_eq!(Ok(560670), sum_numbers_close_to_symbols(&input));
     }
 
     #[test]
     fn it_solves_second_part() {
         let input = read_input_file("input_day03.txt");
 
-        assert_eq!(91622824, sum_gear_ratios(&input));
+        assert_eq!(Ok(91622824), sum_gear_ratios(&input));
     }
 
     #[test]
@@ -196,7 +203,17 @@ mod tests {
         ...$.*....
         .664.598.."};
 
-        assert_eq!(467835, sum_gear_ratios(input));
+        assert_eq!(Ok(467835), sum_gear_ratios(input));
+    }
+
+    #[test]
+    fn it_reports_a_ragged_schematic() {
+        let input = indoc! {"
+        467..114..
+        ...*....
+        ..35..633."};
+
+        assert_eq!(Err(ParseError::new(3, 2, 1, "expected a row of 10 cells, found 8")), sum_numbers_close_to_symbols(input));
     }
 
     #[test]
@@ -213,7 +230,7 @@ mod tests {
         ...$.*....
         .664.598.."};
 
-        assert_eq!(4361, sum_numbers_close_to_symbols(input));
+        assert_eq!(Ok(4361), sum_numbers_close_to_symbols(input));
     }
 
     #[test]
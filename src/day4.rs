@@ -1,27 +1,27 @@
 use std::collections::{HashMap, HashSet};
 
 use regex::Regex;
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Card {
-    index: usize,
-    winning_numbers: HashSet<usize>,
-    numbers: HashSet<usize>
+pub struct Card {
+    pub index: usize,
+    pub winning_numbers: HashSet<usize>,
+    pub numbers: HashSet<usize>
 }
 
 impl Card {
-    fn build_from(raw_card: &str) -> Self {
-        let parts: Vec<_> = raw_card.split(": ").collect();
-        let card_with_index = parts[0];
-        let numbers: Vec<_> = parts[1].split(" | ").collect();
-        let index = parse_card_index(card_with_index);
-        let winning_numbers: HashSet<_> = parse_numbers(numbers[0]);
-        let numbers: HashSet<_> = parse_numbers(numbers[1]);
-        Card { index, winning_numbers, numbers }
+    pub fn build_from(raw_card: &Line) -> Result<Self, ParseError> {
+        let (card_with_index, numbers) = raw_card.split_once(raw_card.text, ": ")?;
+        let (winning_numbers, numbers) = raw_card.split_once(numbers, " | ")?;
+        let index = parse_card_index(raw_card, card_with_index)?;
+        let winning_numbers: HashSet<_> = parse_numbers(raw_card, winning_numbers)?;
+        let numbers: HashSet<_> = parse_numbers(raw_card, numbers)?;
+        Ok(Card { index, winning_numbers, numbers })
     }
 
-    fn score(&self) -> usize {
+    pub fn score(&self) -> usize {
         let number_of_overlapping_cards = self.number_of_overlapping_cards();
         if number_of_overlapping_cards == 0 {
             return 0;
@@ -30,57 +30,64 @@ impl Card {
         1 << (number_of_overlapping_cards - 1)
     }
 
-    fn number_of_overlapping_cards(&self) -> usize {
+    pub fn number_of_overlapping_cards(&self) -> usize {
         self.numbers.intersection(&self.winning_numbers).count()
     }
 }
 
-fn parse_card_index(card_with_index: &str) -> usize {
-    Regex::new(r"(\d+)").unwrap()
+fn parse_card_index(line: &Line, card_with_index: &str) -> Result<usize, ParseError> {
+    Regex::new(r"^Card +(\d+)$").unwrap()
         .captures(card_with_index)
-        .unwrap()
-        .get(1)
-        .unwrap()
-        .as_str()
-        .parse::<usize>()
-        .unwrap()
+        .ok_or_else(|| line.error(card_with_index, format!("expected `Card <index>`, found `{}`", card_with_index)))
+        .and_then(|c| line.parse::<usize>(c.get(1).unwrap().as_str()))
 }
 
-fn parse_numbers(values: &str) -> HashSet<usize> {
-    Regex::new(r"(\d+)")
-        .unwrap()
-        .captures_iter(values)
-        .map(|c| c.extract())
-        .map(|(_, [value])| value.parse::<usize>().unwrap())
+fn parse_numbers<'a>(line: &Line<'a>, values: &'a str) -> Result<HashSet<usize>, ParseError> {
+    values.split_whitespace()
+        .map(|value| line.parse::<usize>(value))
         .collect()
 }
 
-fn deck_score(input: &str) -> usize {
-    cards_from(input)
+pub fn deck_score(input: &str) -> Result<usize, ParseError> {
+    let score = cards_from(input)?
         .iter()
         .map(|c| c.score())
-        .sum()
+        .sum();
+    Ok(score)
 }
 
-fn calculate_number_of_copies(input: &str) -> usize {
+pub fn calculate_number_of_copies(input: &str) -> Result<usize, ParseError> {
     let mut copies: HashMap<usize, usize> = HashMap::new();
 
-    for card in cards_from(input) {
+    for card in cards_from(input)? {
         *copies.entry(card.index).or_insert(0)+=1;
-        let card_copies = copies.get(&card.index).unwrap().clone();
+        let card_copies = *copies.get(&card.index).unwrap();
         for card_index in 1..=card.number_of_overlapping_cards() {
             *copies.entry(card.index + card_index).or_insert(0)+=card_copies;
         }
     }
 
-    copies.values().sum()
+    Ok(copies.values().sum())
 }
 
-fn cards_from(input: &str) -> Vec<Card> {
-    read_lines(input)
-        .iter()
-        .map(|c| Card::build_from(c))
-        .collect()
+fn cards_from(input: &str) -> Result<Vec<Card>, ParseError> {
+    parse_lines(4, input, Card::build_from)
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(cards_from(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(deck_score(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_number_of_copies(input)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -94,14 +101,14 @@ mod tests {
     fn it_solves_first_part() {
         let input = read_input_file("input_day04.txt");
 
-        assert_eq!(24706, deck_score(&input));
+        assert_eq!(Ok(24706), deck_score(&input));
     }
 
     #[test]
     fn it_solves_second_part() {
         let input = read_input_file("input_day04.txt");
 
-        assert_eq!(13114317, calculate_number_of_copies(&input));
+        assert_eq!(Ok(13114317), calculate_number_of_copies(&input));
     }
 
     #[test]
@@ -114,7 +121,7 @@ mod tests {
         Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
         Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"};
 
-        assert_eq!(30, calculate_number_of_copies(&input));
+        assert_eq!(Ok(30), calculate_number_of_copies(input));
     }
 
     #[test]
@@ -127,22 +134,35 @@ mod tests {
         Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
         Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"};
 
-        assert_eq!(13, deck_score(input));
+        assert_eq!(Ok(13), deck_score(input));
     }
 
     #[test]
     fn it_calculates_card_score() {
-        assert_eq!(8, Card::build_from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").score());
-        assert_eq!(2, Card::build_from("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19").score());
-        assert_eq!(1, Card::build_from("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83").score());
-        assert_eq!(0, Card::build_from("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36").score());
+        assert_eq!(8, build_card("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").score());
+        assert_eq!(2, build_card("Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19").score());
+        assert_eq!(1, build_card("Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83").score());
+        assert_eq!(0, build_card("Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36").score());
     }
 
     #[test]
     fn it_parses_a_card() {
-        let card = Card::build_from("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
+        let card = build_card("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53");
         assert_eq!(1, card.index);
         assert_eq!(HashSet::from([48, 41, 86, 83, 17]), card.winning_numbers);
         assert_eq!(HashSet::from([83,86,6,31,17,9,48,53]), card.numbers);
     }
+
+    #[test]
+    fn it_reports_a_malformed_card() {
+        let input = indoc! {"
+        Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+        Card 2: 13 32 2O 16 61 | 61 30 68 82 17 32 24 19"};
+
+        assert_eq!(Err(ParseError::new(4, 2, 15, "expected a number, found `2O`")), deck_score(input));
+    }
+
+    fn build_card(raw_card: &str) -> Card {
+        Card::build_from(&Line::new(4, 1, raw_card)).unwrap()
+    }
 }
\ No newline at end of file
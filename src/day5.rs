@@ -1,21 +1,30 @@
+use crate::parse_error::{Line, lines, ParseError};
+use crate::search::bfs;
+use crate::solution::{Answer, Solution, SolveError};
+
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Range {
-    source: usize,
-    destination: usize,
-    length: usize,
+pub struct Range {
+    pub source: usize,
+    pub destination: usize,
+    pub length: usize,
 }
 
 impl Range {
-    fn build_from(line: &str) -> Self {
-        let parts: Vec<_> = line.split(" ").map(|v| v.parse::<usize>().unwrap()).collect();
-        Range { source: parts[1], destination: parts[0], length: parts[2] }
+    pub fn build_from(line: &Line) -> Result<Self, ParseError> {
+        let parts = line.text.split(' ')
+            .map(|v| line.parse::<usize>(v))
+            .collect::<Result<Vec<_>, _>>()?;
+        if parts.len() != 3 {
+            return Err(line.error(line.text, format!("expected 3 numbers, found {}", parts.len())));
+        }
+        Ok(Range { source: parts[1], destination: parts[0], length: parts[2] })
     }
 
-    fn include(&self, n: usize) -> bool {
+    pub fn include(&self, n: usize) -> bool {
         n >= self.source && n < self.source + self.length
     }
 
-    fn get(&self, n: usize) -> usize {
+    pub fn get(&self, n: usize) -> usize {
         if !self.include(n) {
             panic!("Number out of range!")
         }
@@ -24,20 +33,20 @@ impl Range {
         self.destination + step
     }
 
-    fn reverse(&self) -> Self {
+    pub fn reverse(&self) -> Self {
         Self { destination: self.source, source: self.destination, length: self.length }
     }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Map {
-    source: String,
-    destination: String,
-    ranges: Vec<Range>,
+pub struct Map {
+    pub source: String,
+    pub destination: String,
+    pub ranges: Vec<Range>,
 }
 
 impl Map {
-    fn map_number(&self, number: usize) -> usize {
+    pub fn map_number(&self, number: usize) -> usize {
         if let Some(range) = self.ranges.iter().find(|r| r.include(number)) {
             range.get(number)
         } else {
@@ -45,7 +54,61 @@ impl Map {
         }
     }
 
-    fn reverse(&self) -> Map {
+    // every interval is split at the boundaries of the ranges, numbers outside of any range keep their value
+    pub fn map_ranges(&self, seed_ranges: &[SeedRange]) -> Vec<SeedRange> {
+        let mut unmapped = seed_ranges.to_vec();
+        let mut mapped = vec![];
+
+        for range in self.ranges.iter().filter(|r| r.length > 0) {
+            let source = SeedRange::build_from(range.source, range.length);
+            let mut remaining = vec![];
+            for seed_range in unmapped {
+                let start = seed_range.start.max(source.start);
+                let end = seed_range.end.min(source.end);
+                if start > end {
+                    remaining.push(seed_range);
+                    continue;
+                }
+
+                mapped.push(SeedRange { start: range.get(start), end: range.get(end) });
+                if seed_range.start < start {
+                    remaining.push(SeedRange { start: seed_range.start, end: start - 1 });
+                }
+                if end < seed_range.end {
+                    remaining.push(SeedRange { start: end + 1, end: seed_range.end });
+                }
+            }
+            unmapped = remaining;
+        }
+
+        mapped.extend(unmapped);
+        merge_seed_ranges(mapped)
+    }
+
+    // the source numbers missing between the lowest and the highest range, and those covered by more than one range
+    pub fn gaps_and_overlaps(&self) -> (Vec<SeedRange>, Vec<SeedRange>) {
+        let mut sources: Vec<_> = self.ranges.iter()
+            .filter(|r| r.length > 0)
+            .map(|r| SeedRange::build_from(r.source, r.length))
+            .collect();
+        sources.sort_by_key(|r| r.start);
+
+        let mut gaps = vec![];
+        let mut overlaps = vec![];
+        let mut covered: Option<usize> = None;
+        for source in sources {
+            match covered {
+                Some(end) if source.start > end + 1 => gaps.push(SeedRange { start: end + 1, end: source.start - 1 }),
+                Some(end) if source.start <= end => overlaps.push(SeedRange { start: source.start, end: end.min(source.end) }),
+                _ => {}
+            }
+            covered = Some(covered.map_or(source.end, |end| end.max(source.end)));
+        }
+
+        (gaps, merge_seed_ranges(overlaps))
+    }
+
+    pub fn reverse(&self) -> Map {
         let mut ranges = self.ranges.clone();
         ranges.reverse();
         let ranges = ranges.iter().map(|r| r.reverse()).collect();
@@ -57,105 +120,275 @@ impl Map {
     }
 }
 
-struct Almanac {
-    seeds: Vec<usize>,
-    maps: Vec<Map>,
+// a map whose ranges are sorted and do not overlap, numbers outside of any range keep their value
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PiecewiseMap {
+    pub pieces: Vec<Range>,
 }
 
-impl Almanac {
-    fn calculate_location(&self, seed: usize) -> usize {
-        self.maps.iter()
-            .fold(seed, |curr, map| map.map_number(curr))
+impl PiecewiseMap {
+    // when ranges overlap the first one wins, as in `Map::map_number`
+    pub fn from_map(map: &Map) -> Self {
+        let mut pieces: Vec<Range> = vec![];
+        for range in map.ranges.iter().filter(|r| r.length > 0) {
+            let covered = merge_seed_ranges(pieces.iter().map(|p| SeedRange::build_from(p.source, p.length)).collect());
+            let mut start = range.source;
+            let end = range.source + range.length;
+            for taken in covered.iter().filter(|c| c.end >= range.source && c.start < end) {
+                if taken.start > start {
+                    pieces.push(Range { source: start, destination: range.get(start), length: taken.start - start });
+                }
+                start = start.max(taken.end + 1);
+            }
+            if start < end {
+                pieces.push(Range { source: start, destination: range.get(start), length: end - start });
+            }
+        }
+        PiecewiseMap::new(pieces)
     }
 
-    fn lowest_location(&self) -> usize {
-        self.seeds.iter()
-            .map(|s| self.calculate_location(*s))
-            .min()
-            .unwrap_or(0)
+    fn new(mut pieces: Vec<Range>) -> Self {
+        pieces.sort_by_key(|p| p.source);
+        PiecewiseMap { pieces }.simplified()
+    }
+
+    // drops the pieces that keep their value and joins the adjacent pieces with the same shift
+    pub fn simplified(&self) -> Self {
+        let mut pieces: Vec<Range> = vec![];
+        for piece in self.pieces.iter().filter(|p| p.length > 0 && p.source != p.destination) {
+            match pieces.last_mut() {
+                Some(last) if last.source + last.length == piece.source && last.destination + last.length == piece.destination => {
+                    last.length += piece.length
+                }
+                _ => pieces.push(piece.clone())
+            }
+        }
+        PiecewiseMap { pieces }
+    }
+
+    // binary search over the pieces
+    pub fn get(&self, number: usize) -> usize {
+        let index = self.pieces.partition_point(|p| p.source + p.length <= number);
+        match self.pieces.get(index) {
+            Some(piece) if piece.include(number) => piece.get(number),
+            _ => number
+        }
+    }
+
+    // `self` applied to the numbers from `start`, split where the shift changes
+    fn segments(&self, start: usize, length: usize) -> Vec<Range> {
+        let mut segments = vec![];
+        let mut position = start;
+        let end = start + length;
+        while position < end {
+            let index = self.pieces.partition_point(|p| p.source + p.length <= position);
+            let segment = match self.pieces.get(index) {
+                Some(piece) if piece.include(position) => {
+                    Range { source: position, destination: piece.get(position), length: (piece.source + piece.length).min(end) - position }
+                }
+                Some(piece) => Range { source: position, destination: position, length: piece.source.min(end) - position },
+                None => Range { source: position, destination: position, length: end - position },
+            };
+            position += segment.length;
+            segments.push(segment);
+        }
+        segments
+    }
+
+    fn end(&self) -> usize {
+        self.pieces.last().map_or(0, |p| p.source + p.length)
+    }
+
+    // the map applying `self` first and then `next`
+    pub fn compose(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        // beyond both ends every number keeps its value
+        let limit = self.end().max(next.end());
+        let mut pieces = vec![];
+        for segment in self.segments(0, limit) {
+            for mapped in next.segments(segment.destination, segment.length) {
+                pieces.push(Range {
+                    source: segment.source + (mapped.source - segment.destination),
+                    destination: mapped.destination,
+                    length: mapped.length,
+                });
+            }
+        }
+        PiecewiseMap::new(pieces)
     }
 }
 
-fn parse_almanac(input: Vec<String>) -> Almanac {
-    let conversions = [
-        ("seed", "soil"),
-        ("soil", "fertilizer"),
-        ("fertilizer", "water"),
-        ("water", "light"),
-        ("light", "temperature"),
-        ("temperature", "humidity"),
-        ("humidity", "location")
-    ];
-
-    let seeds: Vec<_> = input[0].split(": ").collect::<Vec<_>>()[1]
-        .split(" ")
-        .map(|v| v.parse::<usize>().unwrap())
-        .collect();
-
-    let mut maps: Vec<Map> = vec![];
-    let mut index = 3;
-    for conversion in conversions {
-        let mut ranges = vec![];
-
-        while index < input.len() && !input[index].is_empty() {
-            ranges.push(Range::build_from(&input[index]));
-            index += 1;
+pub struct Almanac {
+    pub seeds: Vec<usize>,
+    pub maps: Vec<Map>,
+}
+
+impl Almanac {
+    // reads any almanac, the maps can link their categories in any order
+    pub fn parse(input: &str) -> Result<Almanac, ParseError> {
+        let input: Vec<_> = lines(5, input).collect();
+        let seeds_line = input.first()
+            .ok_or_else(|| ParseError::end_of_input(5, 1, "`seeds:`"))?;
+        let (_, raw_seeds) = seeds_line.split_once(seeds_line.text, ": ")?;
+        let seeds = raw_seeds.split(' ')
+            .map(|v| seeds_line.parse::<usize>(v))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut maps: Vec<Map> = vec![];
+        for block in input[1..].split(|line| line.text.is_empty()).filter(|block| !block.is_empty()) {
+            let header = &block[0];
+            let (source, destination) = header.text.strip_suffix(" map:")
+                .and_then(|categories| categories.split_once("-to-"))
+                .ok_or_else(|| header.error(header.text, format!("expected a header like `seed-to-soil map:`, found `{}`", header.text)))?;
+
+            let ranges = block[1..].iter()
+                .map(Range::build_from)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            maps.push(Map { source: source.to_string(), destination: destination.to_string(), ranges });
+        }
+
+        Ok(Almanac { seeds, maps })
+    }
+
+    // the maps to apply one after the other, a map is read backwards to convert from its destination to its source
+    pub fn chain(&self, from: &str, to: &str) -> Option<Vec<Map>> {
+        let categories = bfs(
+            [from.to_string()],
+            |category| self.conversions_from(category).into_iter().map(|map| map.destination),
+            |category| category == to,
+        )?.path;
+
+        categories.windows(2)
+            .map(|pair| self.conversions_from(&pair[0]).into_iter().find(|map| map.destination == pair[1]))
+            .collect()
+    }
+
+    fn conversions_from(&self, category: &str) -> Vec<Map> {
+        let forward = self.maps.iter().filter(|map| map.source == category).cloned();
+        let backward = self.maps.iter().filter(|map| map.destination == category).map(Map::reverse);
+        forward.chain(backward).collect()
+    }
+
+    pub fn convert(&self, from: &str, to: &str, value: usize) -> Option<usize> {
+        let chain = self.chain(from, to)?;
+        Some(chain.iter().fold(value, |curr, map| map.map_number(curr)))
+    }
+
+    // the whole chain as a single map
+    pub fn compose(&self, from: &str, to: &str) -> Option<PiecewiseMap> {
+        let chain = self.chain(from, to)?;
+        Some(chain.iter().fold(PiecewiseMap::default(), |composed, map| composed.compose(&PiecewiseMap::from_map(map))))
+    }
+
+    pub fn calculate_location(&self, seed: usize) -> Option<usize> {
+        self.convert("seed", "location", seed)
+    }
+
+    // the intervals of every category, from the seeds to the locations
+    pub fn trace_seed_ranges(&self, seed_ranges: &[SeedRange]) -> Option<Vec<(String, Vec<SeedRange>)>> {
+        let mut trace = vec![];
+        let mut current = merge_seed_ranges(seed_ranges.to_vec());
+        for map in self.chain("seed", "location")? {
+            let next = map.map_ranges(&current);
+            trace.push((map.source, current));
+            current = next;
         }
+        trace.push(("location".to_string(), current));
+        Some(trace)
+    }
 
-        maps.push(Map {
-            source: conversion.0.to_string(),
-            destination: conversion.1.to_string(),
-            ranges,
-        });
+    pub fn location_ranges(&self, seed_ranges: &[SeedRange]) -> Option<Vec<SeedRange>> {
+        self.trace_seed_ranges(seed_ranges)?.pop().map(|(_, ranges)| ranges)
+    }
+
+    pub fn lowest_location(&self) -> Option<usize> {
+        let seed_to_location = self.compose("seed", "location")?;
+        Some(self.seeds.iter()
+            .map(|s| seed_to_location.get(*s))
+            .min()
+            .unwrap_or(0))
+    }
+}
 
-        index += 2; // skip blank line and map line
+// the puzzle almanac, its maps must lead from the seeds to the locations
+pub fn parse_almanac(input: &str) -> Result<Almanac, ParseError> {
+    let almanac = Almanac::parse(input)?;
+    if almanac.chain("seed", "location").is_none() {
+        return Err(missing_location_maps(input));
     }
+    Ok(almanac)
+}
 
-    Almanac { seeds, maps }
+fn missing_location_maps(input: &str) -> ParseError {
+    ParseError::end_of_input(5, input.lines().count() + 1, "maps from `seed` to `location`")
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct SeedRange {
-    start: usize,
-    end: usize
+pub struct SeedRange {
+    pub start: usize,
+    pub end: usize
 }
 
 impl SeedRange {
-    fn build_from(start: usize, length: usize) -> Self {
+    pub fn build_from(start: usize, length: usize) -> Self {
         SeedRange { start, end: start + length - 1 }
     }
 
-    fn is_including(&self, seed: usize) -> bool{
+    pub fn is_including(&self, seed: usize) -> bool{
         self.start <= seed && seed <= self.end
     }
 }
 
-fn calculate_min_location(ranges: Vec<SeedRange>, almanac: Almanac) -> usize {
-    let mut reversed = almanac.maps.clone();
-    reversed.reverse();
-    let reversed: Vec<_> = reversed.iter().map(|m| m.reverse()).collect();
+// sorted, with the overlapping or adjacent intervals joined
+pub fn merge_seed_ranges(mut seed_ranges: Vec<SeedRange>) -> Vec<SeedRange> {
+    seed_ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<SeedRange> = vec![];
+    for seed_range in seed_ranges {
+        match merged.last_mut() {
+            Some(last) if seed_range.start <= last.end + 1 => last.end = last.end.max(seed_range.end),
+            _ => merged.push(seed_range)
+        }
+    }
+    merged
+}
+
+pub(crate) fn parse_seed_ranges(seeds: &[usize]) -> Vec<SeedRange> {
+    seeds.chunks(2)
+        .map(|pair| SeedRange::build_from(pair[0], pair[1]))
+        .collect()
+}
+
+pub fn lowest_location_for_seed_ranges(input: &str) -> Result<usize, ParseError> {
+    let almanac = parse_almanac(input)?;
+    if almanac.seeds.len() % 2 != 0 {
+        return Err(ParseError::new(5, 1, 1, "expected seeds as pairs of start and length"));
+    }
+    if let Some(pair) = almanac.seeds.chunks(2).find(|pair| pair[1] == 0) {
+        return Err(ParseError::new(5, 1, 1, format!("the seed range starting at {} is empty", pair[0])));
+    }
+    let ranges = parse_seed_ranges(&almanac.seeds);
+    calculate_min_location(ranges, almanac).ok_or_else(|| missing_location_maps(input))
+}
+
+pub fn calculate_min_location(ranges: Vec<SeedRange>, almanac: Almanac) -> Option<usize> {
+    Some(almanac.location_ranges(&ranges)?.first().map_or(0, |r| r.start))
+}
 
-    let location_map = reversed.first().unwrap();
+pub struct Day5;
 
-    // println!("{:?}", reversed);
-    // println!("{:?}", location_map);
+impl Solution for Day5 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_almanac(input).map(|_| ()))
+    }
 
-    let mut lowest_location_range: Range = location_map.ranges.iter().min_by(|r1, r2| r1.source.cmp(&r2.source)).unwrap().clone();
-    if lowest_location_range.source != 0 {
-        lowest_location_range = Range { source: 0, destination: lowest_location_range.source - 1, length: lowest_location_range.source };
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        let lowest = parse_almanac(input)?.lowest_location().ok_or_else(|| missing_location_maps(input))?;
+        Ok(lowest.into())
     }
 
-    // println!("{:?}", lowest_location_range);
-    let mut min_location: Option<usize> = None;
-    for location in lowest_location_range.source..=lowest_location_range.destination {
-        let seed = reversed.iter().fold(location, |curr, map| map.map_number(curr));
-        if ranges.iter().any(|r| r.is_including(seed)) {
-            min_location = Some(location);
-            break;
-        }
-        // println!("{:?} location {:?}", seed, location);
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(lowest_location_for_seed_ranges(input)?.into())
     }
-    min_location.unwrap()
 }
 
 #[cfg(test)]
@@ -163,13 +396,12 @@ mod tests {
     use indoc::indoc;
 
     use crate::day5::*;
-    use crate::input_reader::{read_input_file, read_lines};
+    use crate::input_reader::read_input_file;
 
     #[test]
-    #[ignore] // slow!! it completes in > 2 minutes
     fn it_solves_second_part() {
         let input = read_input_file("input_day05.txt");
-        let almanac = parse_almanac(read_lines(&input));
+        let almanac = parse_almanac(&input).unwrap();
         let ranges = [
             SeedRange::build_from(2880930400,17599561),
             SeedRange::build_from(549922357,200746426),
@@ -184,16 +416,20 @@ mod tests {
         ];
 
         let min_location = calculate_min_location(ranges.to_vec(), almanac);
-        println!("min location {:?}", min_location); // 78775051
+        assert_eq!(Some(78775051), min_location);
+        assert_eq!(Ok(78775051), lowest_location_for_seed_ranges(&input));
+        let seeds = input.lines().next().unwrap();
+        assert_eq!(Err(ParseError::new(5, 1, 1, "the seed range starting at 0 is empty")), lowest_location_for_seed_ranges(&input.replacen(seeds, "seeds: 0 0", 1)));
+        assert_eq!(Err(ParseError::new(5, 1, 1, "the seed range starting at 79 is empty")), lowest_location_for_seed_ranges(&input.replacen(seeds, "seeds: 79 0", 1)));
     }
 
     #[test]
     fn it_solves_first_part() {
         let input = read_input_file("input_day05.txt");
 
-        let almanac = parse_almanac(read_lines(&input));
+        let almanac = parse_almanac(&input).unwrap();
 
-        assert_eq!(227653707, almanac.lowest_location());
+        assert_eq!(Some(227653707), almanac.lowest_location());
     }
 
     #[test]
@@ -238,10 +474,15 @@ mod tests {
             SeedRange::build_from(55,13)
         ];
 
-        let almanac = parse_almanac(read_lines(input));
+        let almanac = parse_almanac(input).unwrap();
+
+        let trace = almanac.trace_seed_ranges(&ranges).unwrap();
+        assert_eq!(8, trace.len());
+        assert_eq!(("soil".to_string(), vec![SeedRange { start: 57, end: 69 }, SeedRange { start: 81, end: 94 }]), trace[1]);
+        assert_eq!("location", trace[7].0);
 
         let min_location = calculate_min_location(ranges.to_vec(), almanac);
-        assert_eq!(46, min_location);
+        assert_eq!(Some(46), min_location);
     }
 
     #[test]
@@ -281,10 +522,10 @@ mod tests {
         60 56 37
         56 93 4"};
 
-        let almanac = parse_almanac(read_lines(input));
+        let almanac = parse_almanac(input).unwrap();
 
-        assert_eq!(82, almanac.calculate_location(79));
-        assert_eq!(35, almanac.lowest_location());
+        assert_eq!(Some(82), almanac.calculate_location(79));
+        assert_eq!(Some(35), almanac.lowest_location());
     }
 
     #[test]
@@ -324,7 +565,7 @@ mod tests {
         60 56 37
         56 93 4"};
 
-        let almanac = parse_almanac(read_lines(input));
+        let almanac = parse_almanac(input).unwrap();
 
         assert_eq!(vec![79, 14, 55, 13], almanac.seeds);
         assert_eq!(Map {
@@ -362,21 +603,167 @@ mod tests {
         assert_eq!(13, map.map_number(13));
     }
 
+    #[test]
+    fn it_maps_ranges() {
+        let map = Map {
+            source: "seed".to_string(),
+            destination: "soil".to_string(),
+            ranges: vec![
+                Range { source: 98, destination: 50, length: 2 },
+                Range { source: 50, destination: 52, length: 48 },
+            ],
+        };
+
+        assert_eq!(vec![SeedRange { start: 57, end: 69 }, SeedRange { start: 81, end: 94 }],
+                   map.map_ranges(&[SeedRange::build_from(79, 14), SeedRange::build_from(55, 13)]));
+        assert_eq!(vec![SeedRange { start: 40, end: 51 }, SeedRange { start: 100, end: 105 }],
+                   map.map_ranges(&[SeedRange { start: 40, end: 49 }, SeedRange { start: 98, end: 105 }]));
+    }
+
     #[test]
     fn it_parses_a_range() {
         assert_eq!(
             Range { source: 98, destination: 50, length: 2 },
-            Range::build_from("50 98 2")
+            Range::build_from(&Line::new(5, 4, "50 98 2")).unwrap()
+        );
+        assert_eq!(
+            Err(ParseError::new(5, 4, 1, "expected 3 numbers, found 2")),
+            Range::build_from(&Line::new(5, 4, "50 98"))
+        );
+    }
+
+    #[test]
+    fn it_reports_an_unexpected_map() {
+        let input = indoc! {"
+        seeds: 79 14 55 13
+
+        seed-to-soil map:
+        50 98 2
+
+        soil-to-water map:
+        0 15 37"};
+
+        assert_eq!(
+            Err(ParseError::end_of_input(5, 8, "maps from `seed` to `location`")),
+            parse_almanac(input).map(|almanac| almanac.seeds)
+        );
+        assert_eq!(
+            Err(ParseError::new(5, 3, 1, "expected a header like `seed-to-soil map:`, found `seed to soil:`")),
+            parse_almanac("seeds: 79\n\nseed to soil:\n50 98 2").map(|almanac| almanac.seeds)
+        );
+    }
+
+    #[test]
+    fn it_composes_maps() {
+        let input = indoc! {"
+        seeds: 79 14 55 13
+
+        seed-to-soil map:
+        50 98 2
+        52 50 48
+
+        soil-to-fertilizer map:
+        0 15 37
+        37 52 2
+        39 0 15
+
+        fertilizer-to-water map:
+        49 53 8
+        0 11 42
+        42 0 7
+        57 7 4
+
+        water-to-light map:
+        88 18 7
+        18 25 70
+
+        light-to-temperature map:
+        45 77 23
+        81 45 19
+        68 64 13
+
+        temperature-to-humidity map:
+        0 69 1
+        1 0 69
+
+        humidity-to-location map:
+        60 56 37
+        56 93 4"};
+
+        let almanac = parse_almanac(input).unwrap();
+        let seed_to_location = almanac.compose("seed", "location").unwrap();
+
+        assert!((0..200).all(|seed| Some(seed_to_location.get(seed)) == almanac.calculate_location(seed)));
+        assert!(seed_to_location.pieces.windows(2).all(|p| p[0].source + p[0].length <= p[1].source));
+        assert_eq!(Some(PiecewiseMap::default()), almanac.compose("soil", "soil"));
+    }
+
+    #[test]
+    fn it_simplifies_maps() {
+        let map = Map {
+            source: "a".to_string(),
+            destination: "b".to_string(),
+            ranges: vec![
+                Range { source: 10, destination: 20, length: 5 },
+                Range { source: 15, destination: 25, length: 5 },
+                Range { source: 30, destination: 30, length: 5 },
+                Range { source: 18, destination: 0, length: 4 },
+            ],
+        };
+
+        assert_eq!(PiecewiseMap {
+            pieces: vec![
+                Range { source: 10, destination: 20, length: 10 },
+                Range { source: 20, destination: 2, length: 2 },
+            ]
+        }, PiecewiseMap::from_map(&map));
+        assert_eq!(
+            (vec![SeedRange { start: 22, end: 29 }], vec![SeedRange { start: 18, end: 19 }]),
+            map.gaps_and_overlaps()
         );
+        assert!((0..40).all(|n| PiecewiseMap::from_map(&map).get(n) == map.map_number(n)));
+    }
+
+    #[test]
+    fn it_converts_between_categories() {
+        let input = indoc! {"
+        seeds: 79 14 55 13
+
+        soil-to-fertilizer map:
+        0 15 37
+        37 52 2
+        39 0 15
+
+        seed-to-soil map:
+        50 98 2
+        52 50 48
+
+        celsius-to-fahrenheit map:
+        32 0 100"};
+
+        let almanac = Almanac::parse(input).unwrap();
+
+        assert_eq!(Some(81), almanac.convert("seed", "soil", 79));
+        assert_eq!(Some(81), almanac.convert("seed", "fertilizer", 79));
+        assert_eq!(Some(79), almanac.convert("fertilizer", "seed", 81));
+        assert_eq!(Some(98), almanac.convert("soil", "seed", 50));
+        assert_eq!(Some(5), almanac.convert("seed", "seed", 5));
+        assert_eq!(None, almanac.convert("seed", "fahrenheit", 79));
+        assert_eq!(vec!["seed", "soil"], almanac.chain("seed", "fertilizer").unwrap().iter().map(|m| m.source.as_str()).collect::<Vec<_>>());
+
+        // there is no way to the locations
+        assert_eq!(None, almanac.calculate_location(79));
+        assert_eq!(None, almanac.lowest_location());
+        assert_eq!(None, almanac.location_ranges(&parse_seed_ranges(&almanac.seeds)));
     }
 
     #[test]
     fn it_finds_if_number_in_range() {
         let range = Range { source: 98, destination: 50, length: 2 };
-        assert_eq!(false, range.include(97));
-        assert_eq!(true, range.include(98));
-        assert_eq!(true, range.include(99));
-        assert_eq!(false, range.include(100));
+        assert!(!range.include(97));
+        assert!(range.include(98));
+        assert!(range.include(99));
+        assert!(!range.include(100));
     }
 
     #[test]
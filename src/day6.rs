@@ -1,7 +1,10 @@
-fn calculate_record_breaks(times: Vec<usize>, distance: Vec<usize>) -> usize {
+use crate::parse_error::{Line, lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
+
+pub fn calculate_record_breaks(times: Vec<usize>, distance: Vec<usize>) -> usize {
     times.iter().zip(distance)
         .map(|(&t, d)| calculate_winning_combinations(t, d))
-        .fold(1, |curr, acc| acc * curr)
+        .product()
 }
 
 fn calculate_winning_combinations(time: usize, distance: usize) -> usize {
@@ -12,8 +15,54 @@ fn calculate_winning_combinations(time: usize, distance: usize) -> usize {
         .count()
 }
 
+pub fn parse_races(input: &str, ignore_spaces: bool) -> Result<(Vec<usize>, Vec<usize>), ParseError> {
+    let mut lines = lines(6, input);
+    let times = lines.next().ok_or_else(|| ParseError::end_of_input(6, 1, "`Time:`"))?;
+    let distances_line = lines.next().ok_or_else(|| ParseError::end_of_input(6, 2, "`Distance:`"))?;
+    let (times, distances) = (parse_values(&times, ignore_spaces)?, parse_values(&distances_line, ignore_spaces)?);
+    if times.len() != distances.len() {
+        let (_, values) = distances_line.split_once(distances_line.text, ":")?;
+        return Err(distances_line.error(values, format!("expected {} distances, found {}", times.len(), distances.len())));
+    }
+    Ok((times, distances))
+}
+
+fn parse_values(line: &Line, ignore_spaces: bool) -> Result<Vec<usize>, ParseError> {
+    let (_, values) = line.split_once(line.text, ":")?;
+    if ignore_spaces {
+        let value = values.replace(' ', "");
+        return value.parse::<usize>()
+            .map(|v| vec![v])
+            .map_err(|_| line.error(values, format!("expected a number, found `{}`", value)));
+    }
+
+    values.split_whitespace()
+        .map(|v| line.parse::<usize>(v))
+        .collect()
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_races(input, false).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        let (times, distances) = parse_races(input, false)?;
+        Ok(calculate_record_breaks(times, distances).into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        let (times, distances) = parse_races(input, true)?;
+        Ok(calculate_record_breaks(times, distances).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use indoc::indoc;
+
     use crate::day6::*;
 
     #[test]
@@ -30,6 +79,18 @@ mod tests {
         assert_eq!(43364472, calculate_record_breaks(times, distance));
     }
 
+    #[test]
+    fn it_parses_races() {
+        let input = indoc! {"
+        Time:      7  15   30
+        Distance:  9  40  200"};
+
+        assert_eq!(Ok((vec![7, 15, 30], vec![9, 40, 200])), parse_races(input, false));
+        assert_eq!(Ok((vec![71530], vec![940200])), parse_races(input, true));
+        assert_eq!(Err(ParseError::new(6, 2, 1, "expected `Distance:`, found the end of the input")), parse_races("Time: 7", false));
+        assert_eq!(Err(ParseError::new(6, 2, 10, "expected 3 distances, found 2")), parse_races("Time: 7 15 30\nDistance: 9 40", false));
+    }
+
     #[test]
     fn it_calculates_record_breaks() {
         let times = vec![7, 15, 30];
@@ -4,11 +4,12 @@ use std::collections::HashMap;
 use itertools::Itertools;
 
 use crate::day7::HandType::{FiveOfAKind, FourOfAKind, FullHouse, HighCard, OnePair, ThreeOfAKind, TwoPairs};
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(usize)]
-enum HandType {
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPairs,
@@ -19,21 +20,27 @@ enum HandType {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Hand {
-    cards: Vec<char>,
-    bid: usize,
-    hand_type: HandType,
+pub struct Hand {
+    pub cards: Vec<char>,
+    pub bid: usize,
+    pub hand_type: HandType,
 }
 
 impl Hand {
-    fn new(hand: &str) -> Self {
+    pub fn new(hand: &Line) -> Result<Self, ParseError> {
         Self::build_with(hand, find_hand_type)
     }
 
-    fn build_with(hand: &str, find_hand_type: fn(HashMap<&char, usize>) -> HandType) -> Hand {
-        let parts: Vec<_> = hand.split(" ").collect();
-        let cards: Vec<_> = parts[0].chars().collect();
-        let bid = parts[1].parse::<usize>().unwrap();
+    pub fn build_with(hand: &Line, find_hand_type: fn(HashMap<&char, usize>) -> HandType) -> Result<Hand, ParseError> {
+        let (raw_cards, bid) = hand.split_once(hand.text, " ")?;
+        if raw_cards.len() != 5 {
+            return Err(hand.error(raw_cards, format!("expected 5 cards, found `{}`", raw_cards)));
+        }
+        if let Some((index, card)) = raw_cards.char_indices().find(|(_, c)| !CARD_ORDER.contains(c)) {
+            return Err(hand.error(&raw_cards[index..], format!("unexpected card `{}`", card)));
+        }
+        let cards: Vec<_> = raw_cards.chars().collect();
+        let bid = hand.parse::<usize>(bid)?;
 
         let card_occurrences: HashMap<_, _> = cards.iter()
             .sorted()
@@ -44,11 +51,11 @@ impl Hand {
 
         let hand_type = find_hand_type(card_occurrences);
 
-        Hand { cards, bid, hand_type }
+        Ok(Hand { cards, bid, hand_type })
     }
 }
 
-fn find_hand_type_with_jolly(mut card_occurrences: HashMap<&char, usize>) -> HandType {
+pub fn find_hand_type_with_jolly(mut card_occurrences: HashMap<&char, usize>) -> HandType {
     if !card_occurrences.contains_key(&'J') {
         return find_hand_type(card_occurrences);
     }
@@ -92,7 +99,7 @@ fn find_hand_type_with_jolly(mut card_occurrences: HashMap<&char, usize>) -> Han
     }
 }
 
-fn find_hand_type(card_occurrences: HashMap<&char, usize>) -> HandType {
+pub fn find_hand_type(card_occurrences: HashMap<&char, usize>) -> HandType {
     let occurrences = score_occurrences(card_occurrences);
 
     if occurrences.contains_key(&5) {
@@ -123,20 +130,18 @@ fn find_hand_type(card_occurrences: HashMap<&char, usize>) -> HandType {
 }
 
 fn score_occurrences(card_occurrences: HashMap<&char, usize>) -> HashMap<usize, usize> {
-    card_occurrences
-        .iter()
-        .map(|(_, v)| v)
+    card_occurrences.values()
         .sorted()
         .group_by(|&x| x)
         .into_iter()
-        .map(|(k, v)| (k.clone(), v.count()))
+        .map(|(k, v)| (*k, v.count()))
         .collect()
 }
 
-const CARD_ORDER: [char; 13] = ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'];
-const CARD_ORDER_WITH_JOLLY: [char; 13] = ['J', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'Q', 'K', 'A'];
+pub const CARD_ORDER: [char; 13] = ['2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A'];
+pub const CARD_ORDER_WITH_JOLLY: [char; 13] = ['J', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'Q', 'K', 'A'];
 
-fn hand_comparator(current: &Hand, other: &Hand, card_order: [char; 13]) -> Ordering {
+pub fn hand_comparator(current: &Hand, other: &Hand, card_order: [char; 13]) -> Ordering {
     if current.hand_type != other.hand_type {
         return current.hand_type.cmp(&other.hand_type);
     }
@@ -149,16 +154,33 @@ fn hand_comparator(current: &Hand, other: &Hand, card_order: [char; 13]) -> Orde
         }
     }
 
-    return Ordering::Equal;
+    Ordering::Equal
 }
 
-fn total_winning(input: &str, card_order: [char; 13], hand_builder: fn(&String) -> Hand) -> usize {
-    read_lines(input).iter()
-        .map(hand_builder)
+pub fn total_winning(input: &str, card_order: [char; 13], hand_builder: fn(&Line) -> Result<Hand, ParseError>) -> Result<usize, ParseError> {
+    let total = parse_lines(7, input, hand_builder)?
+        .iter()
         .sorted_by(|current, other| hand_comparator(current, other, card_order))
         .enumerate()
         .map(|(rank, h)| h.bid * (rank + 1))
-        .sum()
+        .sum();
+    Ok(total)
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_lines(7, input, Hand::new).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(total_winning(input, CARD_ORDER, Hand::new)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(total_winning(input, CARD_ORDER_WITH_JOLLY, |h| Hand::build_with(h, find_hand_type_with_jolly))?.into())
+    }
 }
 
 #[cfg(test)]
@@ -172,14 +194,14 @@ mod tests {
     fn it_solves_first_part() {
         let input = read_input_file("input_day07.txt");
 
-        assert_eq!(251287184, total_winning(&input, CARD_ORDER, |h| Hand::new(h)));
+        assert_eq!(Ok(251287184), total_winning(&input, CARD_ORDER, Hand::new));
     }
 
     #[test]
     fn it_solves_second_part() {
         let input = read_input_file("input_day07.txt");
 
-        assert_eq!(250757288, total_winning(&input, CARD_ORDER_WITH_JOLLY, |h| Hand::build_with(h, find_hand_type_with_jolly)));
+        assert_eq!(Ok(250757288), total_winning(&input, CARD_ORDER_WITH_JOLLY, |h| Hand::build_with(h, find_hand_type_with_jolly)));
     }
 
     #[test]
@@ -191,7 +213,7 @@ mod tests {
         KTJJT 220
         QQQJA 483"};
 
-        assert_eq!(5905, total_winning(input, CARD_ORDER_WITH_JOLLY, |h| Hand::build_with(h, find_hand_type_with_jolly)));
+        assert_eq!(Ok(5905), total_winning(input, CARD_ORDER_WITH_JOLLY, |h| Hand::build_with(h, find_hand_type_with_jolly)));
     }
 
     #[test]
@@ -203,16 +225,16 @@ mod tests {
         KTJJT 220
         QQQJA 483"};
 
-        assert_eq!(6440, total_winning(input, CARD_ORDER, |h| Hand::new(h)));
+        assert_eq!(Ok(6440), total_winning(input, CARD_ORDER, Hand::new));
     }
 
     #[test]
     fn it_parses_a_hand_with_jolly() {
-        assert_eq!(Hand { cards: vec!['3', '2', 'T', '3', 'K'], bid: 765, hand_type: OnePair }, Hand::build_with("32T3K 765", find_hand_type_with_jolly));
-        assert_eq!(Hand { cards: vec!['T', '5', '5', 'J', '5'], bid: 684, hand_type: FourOfAKind }, Hand::build_with("T55J5 684", find_hand_type_with_jolly));
-        assert_eq!(Hand { cards: vec!['K', 'K', '6', '7', '7'], bid: 28, hand_type: TwoPairs }, Hand::build_with("KK677 28", find_hand_type_with_jolly));
-        assert_eq!(Hand { cards: vec!['K', 'T', 'J', 'J', 'T'], bid: 220, hand_type: FourOfAKind }, Hand::build_with("KTJJT 220", find_hand_type_with_jolly));
-        assert_eq!(Hand { cards: vec!['Q', 'Q', 'Q', 'J', 'A'], bid: 483, hand_type: FourOfAKind }, Hand::build_with("QQQJA 483", find_hand_type_with_jolly));
+        assert_eq!(Hand { cards: vec!['3', '2', 'T', '3', 'K'], bid: 765, hand_type: OnePair }, Hand::build_with(&line("32T3K 765"), find_hand_type_with_jolly).unwrap());
+        assert_eq!(Hand { cards: vec!['T', '5', '5', 'J', '5'], bid: 684, hand_type: FourOfAKind }, Hand::build_with(&line("T55J5 684"), find_hand_type_with_jolly).unwrap());
+        assert_eq!(Hand { cards: vec!['K', 'K', '6', '7', '7'], bid: 28, hand_type: TwoPairs }, Hand::build_with(&line("KK677 28"), find_hand_type_with_jolly).unwrap());
+        assert_eq!(Hand { cards: vec!['K', 'T', 'J', 'J', 'T'], bid: 220, hand_type: FourOfAKind }, Hand::build_with(&line("KTJJT 220"), find_hand_type_with_jolly).unwrap());
+        assert_eq!(Hand { cards: vec!['Q', 'Q', 'Q', 'J', 'A'], bid: 483, hand_type: FourOfAKind }, Hand::build_with(&line("QQQJA 483"), find_hand_type_with_jolly).unwrap());
     }
 
     #[test]
@@ -223,10 +245,19 @@ mod tests {
 
     #[test]
     fn it_parses_a_hand() {
-        assert_eq!(Hand { cards: vec!['3', '2', 'T', '3', 'K'], bid: 765, hand_type: OnePair }, Hand::new("32T3K 765"));
-        assert_eq!(Hand { cards: vec!['T', '5', '5', 'J', '5'], bid: 684, hand_type: ThreeOfAKind }, Hand::new("T55J5 684"));
-        assert_eq!(Hand { cards: vec!['K', 'K', '6', '7', '7'], bid: 28, hand_type: TwoPairs }, Hand::new("KK677 28"));
-        assert_eq!(Hand { cards: vec!['K', 'T', 'J', 'J', 'T'], bid: 220, hand_type: TwoPairs }, Hand::new("KTJJT 220"));
-        assert_eq!(Hand { cards: vec!['Q', 'Q', 'Q', 'J', 'A'], bid: 483, hand_type: ThreeOfAKind }, Hand::new("QQQJA 483"));
+        assert_eq!(Hand { cards: vec!['3', '2', 'T', '3', 'K'], bid: 765, hand_type: OnePair }, Hand::new(&line("32T3K 765")).unwrap());
+        assert_eq!(Hand { cards: vec!['T', '5', '5', 'J', '5'], bid: 684, hand_type: ThreeOfAKind }, Hand::new(&line("T55J5 684")).unwrap());
+        assert_eq!(Hand { cards: vec!['K', 'K', '6', '7', '7'], bid: 28, hand_type: TwoPairs }, Hand::new(&line("KK677 28")).unwrap());
+        assert_eq!(Hand { cards: vec!['K', 'T', 'J', 'J', 'T'], bid: 220, hand_type: TwoPairs }, Hand::new(&line("KTJJT 220")).unwrap());
+        assert_eq!(Hand { cards: vec!['Q', 'Q', 'Q', 'J', 'A'], bid: 483, hand_type: ThreeOfAKind }, Hand::new(&line("QQQJA 483")).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_reports_an_unexpected_card() {
+        assert_eq!(Err(ParseError::new(7, 1, 3, "unexpected card `X`")), Hand::new(&line("32X3K 765")));
+    }
+
+    fn line(text: &str) -> Line<'_> {
+        Line::new(7, 1, text)
+    }
+}
@@ -1,42 +1,66 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use num::integer::lcm;
 
 use regex::Regex;
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
-fn parse_node(line: &str) -> (String, (String, String)) {
-    let node_regex = Regex::new(r"(.+) = \((.+), (.+)\)").unwrap();
-    let capture = node_regex.captures(line).unwrap();
+pub type Network = HashMap<String, (String, String)>;
+
+pub fn parse_node(line: &Line) -> Result<(String, (String, String)), ParseError> {
+    let node_regex = Regex::new(r"^(\w+) = \((\w+), (\w+)\)$").unwrap();
+    let capture = node_regex.captures(line.text)
+        .ok_or_else(|| line.error(line.text, format!("expected `<node> = (<left>, <right>)`, found `{}`", line.text)))?;
     let start = String::from(capture.get(1).unwrap().as_str());
     let left = String::from(capture.get(2).unwrap().as_str());
     let right = String::from(capture.get(3).unwrap().as_str());
-    (start, (left, right))
+    Ok((start, (left, right)))
 }
 
-fn parse_network(row_instructions: Vec<&String>) -> HashMap<String, (String, String)> {
-    row_instructions.iter().map(|n| parse_node(n)).collect()
+pub fn parse_network(row_instructions: &[Line]) -> Result<Network, ParseError> {
+    let nodes = row_instructions.iter()
+        .map(parse_node)
+        .collect::<Result<Vec<_>, _>>()?;
+    let network: HashMap<_, _> = nodes.iter().cloned().collect();
+
+    for (line, (_, (left, right))) in row_instructions.iter().zip(&nodes) {
+        if let Some(unknown) = [left, right].into_iter().find(|n| !network.contains_key(*n)) {
+            let at = &line.text[line.text.rfind(unknown.as_str()).unwrap()..];
+            return Err(line.error(at, format!("unknown node `{}`", unknown)));
+        }
+    }
+
+    Ok(network)
 }
 
-fn parse_input(input: &str) -> (String, HashMap<String, (String, String)>) {
-    let lines = read_lines(&input);
-    let instructions = lines.get(0).unwrap().clone();
-    let row_instructions = lines[2..].iter().clone().collect();
-    let network: HashMap<_, _> = parse_network(row_instructions);
-    (instructions, network)
+pub fn parse_input(input: &str) -> Result<(String, Network), ParseError> {
+    let lines: Vec<_> = lines(8, input).collect();
+    let instructions = lines.first()
+        .ok_or_else(|| ParseError::end_of_input(8, 1, "instructions"))?;
+    if instructions.text.is_empty() {
+        return Err(instructions.error(instructions.text, "expected at least one instruction"));
+    }
+    if let Some(index) = instructions.text.find(|c| c != 'L' && c != 'R') {
+        let at = &instructions.text[index..];
+        return Err(instructions.error(at, format!("unexpected instruction `{}`", at.chars().next().unwrap())));
+    }
+    let network = parse_network(lines.get(2..).unwrap_or_default())?;
+    Ok((instructions.text.to_string(), network))
 }
 
-fn calculate_steps(
+pub fn calculate_steps(
     instructions: &str,
     network: HashMap<String, (String, String)>,
     select_start_nodes: fn(&str) -> bool,
     is_end_node: fn(&str) -> bool,
-) -> usize {
+) -> Result<usize, SolveError> {
     network.keys()
         .filter(|node| select_start_nodes(node.as_str()))
         .map(|current_node|
-            calculate_step_for_single_node(instructions, &network, is_end_node, &mut current_node.clone())
-        ).reduce(lcm)
-        .unwrap()
+            calculate_step_for_single_node(instructions, &network, is_end_node, current_node)
+        )
+        .reduce(|a, b| Ok(lcm(a?, b?)))
+        .unwrap_or_else(|| Err(SolveError::unsolvable(8, "there is no start node")))
 }
 
 fn calculate_step_for_single_node<'a>(
@@ -44,15 +68,18 @@ fn calculate_step_for_single_node<'a>(
     network: &'a HashMap<String, (String, String)>,
     is_end_node: fn(&str) -> bool,
     mut current_node: &'a str,
-) -> usize {
-    let mut steps = 0;
-
-    for instruction in instructions.chars().into_iter().cycle() {
+) -> Result<usize, SolveError> {
+    let start = current_node;
+    // being on the same node at the same instruction again means the walk goes round in circles
+    let mut visited = HashSet::new();
+    for (steps, (index, instruction)) in instructions.chars().enumerate().cycle().enumerate() {
         if is_end_node(current_node) {
-            return steps;
+            return Ok(steps);
+        }
+        if !visited.insert((current_node, index)) {
+            break;
         }
 
-        steps += 1;
         let (left, right) = &network[current_node];
         if instruction == 'L' {
             current_node = left.as_str();
@@ -61,26 +88,44 @@ fn calculate_step_for_single_node<'a>(
         }
     }
 
-    panic!("ZZZ not found");
+    Err(SolveError::unsolvable(8, format!("no end node can be reached from `{}`", start)))
 }
 
-fn is_ending_with_an_a(node: &str) -> bool {
+pub(crate) fn is_ending_with_an_a(node: &str) -> bool {
     node.ends_with('A')
 }
 
-fn is_ending_with_an_z(node: &str) -> bool {
+pub(crate) fn is_ending_with_an_z(node: &str) -> bool {
     node.ends_with('Z')
 }
 
-fn is_aaa_node(node: &str) -> bool {
+pub(crate) fn is_aaa_node(node: &str) -> bool {
     node == "AAA"
 }
 
 
-fn has_reached_zzz(node: &str) -> bool {
+pub(crate) fn has_reached_zzz(node: &str) -> bool {
     node == "ZZZ"
 }
 
+pub struct Day8;
+
+impl Solution for Day8 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_input(input).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        let (instructions, network) = parse_input(input)?;
+        Ok(calculate_steps(&instructions, network, is_aaa_node, has_reached_zzz)?.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        let (instructions, network) = parse_input(input)?;
+        Ok(calculate_steps(&instructions, network, is_ending_with_an_a, is_ending_with_an_z)?.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -92,16 +137,16 @@ mod tests {
     fn it_solves_first_part() {
         let input = read_input_file("input_day08.txt");
 
-        let (instructions, network) = parse_input(&input);
-        assert_eq!(13301, calculate_steps(&instructions, network, is_aaa_node, has_reached_zzz));
+        let (instructions, network) = parse_input(&input).unwrap();
+        assert_eq!(Ok(13301), calculate_steps(&instructions, network, is_aaa_node, has_reached_zzz));
     }
 
     #[test]
     fn it_solves_second_part() {
         let input = read_input_file("input_day08.txt");
 
-        let (instructions, network) = parse_input(&input);
-        assert_eq!(7309459565207, calculate_steps(&instructions, network, is_ending_with_an_a, is_ending_with_an_z));
+        let (instructions, network) = parse_input(&input).unwrap();
+        assert_eq!(Ok(7309459565207), calculate_steps(&instructions, network, is_ending_with_an_a, is_ending_with_an_z));
     }
 
     #[test]
@@ -118,8 +163,8 @@ mod tests {
         22Z = (22B, 22B)
         XXX = (XXX, XXX)"};
 
-        let (instructions, network) = parse_input(input);
-        assert_eq!(6, calculate_steps(&instructions, network, is_ending_with_an_a, is_ending_with_an_z));
+        let (instructions, network) = parse_input(input).unwrap();
+        assert_eq!(Ok(6), calculate_steps(&instructions, network, is_ending_with_an_a, is_ending_with_an_z));
     }
 
     #[test]
@@ -136,7 +181,31 @@ mod tests {
         ZZZ = (ZZZ, ZZZ)"};
 
 
-        let (instructions, network) = parse_input(input);
-        assert_eq!(2, calculate_steps(&instructions, network, is_aaa_node, has_reached_zzz));
+        let (instructions, network) = parse_input(input).unwrap();
+        assert_eq!(Ok(2), calculate_steps(&instructions, network, is_aaa_node, has_reached_zzz));
+    }
+
+    #[test]
+    fn it_reports_a_malformed_network() {
+        let input = indoc! {"
+        RL
+
+        AAA = (BBB, ZZZ)
+        BBB = BBB, BBB
+        ZZZ = (ZZZ, ZZZ)"};
+
+        assert_eq!(Err(ParseError::new(8, 4, 1, "expected `<node> = (<left>, <right>)`, found `BBB = BBB, BBB`")), parse_input(input));
+        assert_eq!(Err(ParseError::new(8, 3, 13, "unknown node `CCC`")), parse_input("L\n\nAAA = (AAA, CCC)"));
+        assert_eq!(Err(ParseError::new(8, 1, 2, "unexpected instruction `X`")), parse_input("LX\n\nAAA = (AAA, AAA)"));
+    }
+
+    #[test]
+    fn it_reports_unreachable_end_nodes() {
+        assert_eq!(Err(SolveError::unsolvable(8, "there is no start node")), Day8.part2("L\n\nBBB = (BBB, BBB)"));
+        assert_eq!(Err(SolveError::unsolvable(8, "there is no start node")), Day8.part1("L\n\nBBB = (BBB, BBB)"));
+        assert_eq!(
+            Err(SolveError::unsolvable(8, "no end node can be reached from `AAA`")),
+            Day8.part1("LR\n\nAAA = (BBB, BBB)\nBBB = (BBB, BBB)")
+        );
     }
-}
\ No newline at end of file
+}
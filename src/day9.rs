@@ -1,10 +1,11 @@
 use num::Zero;
 
-use crate::input_reader::read_lines;
+use crate::parse_error::{Line, parse_lines, ParseError};
+use crate::solution::{Answer, Solution, SolveError};
 
-fn find_next_values(mut sequence: Vec<isize>) -> (isize, isize) {
-    let mut first_numbers = vec![sequence.first().unwrap().clone()];
-    let mut last_numbers = vec![sequence.last().unwrap().clone()];
+pub fn find_next_values(mut sequence: Vec<isize>) -> (isize, isize) {
+    let mut first_numbers = vec![*sequence.first().unwrap()];
+    let mut last_numbers = vec![*sequence.last().unwrap()];
 
     loop {
         let new_sequence: Vec<_> = sequence.windows(2)
@@ -16,8 +17,8 @@ fn find_next_values(mut sequence: Vec<isize>) -> (isize, isize) {
         }
 
         sequence = new_sequence;
-        first_numbers.push(sequence.first().unwrap().clone());
-        last_numbers.push(sequence.last().unwrap().clone());
+        first_numbers.push(*sequence.first().unwrap());
+        last_numbers.push(*sequence.last().unwrap());
     }
 
     (
@@ -26,17 +27,34 @@ fn find_next_values(mut sequence: Vec<isize>) -> (isize, isize) {
     )
 }
 
-fn calculate_sum_of_next_values(input: &str) -> (isize, isize) {
-    read_lines(input).iter()
-        .map(|sequence| parse_sequence(sequence))
-        .map(|sequence| find_next_values(sequence))
-        .fold((0, 0), |(acc0, acc1), (e0, e1)| (acc0 + e0, acc1 + e1))
+pub fn calculate_sum_of_next_values(input: &str) -> Result<(isize, isize), ParseError> {
+    let sum = parse_lines(9, input, parse_sequence)?
+        .into_iter()
+        .map(find_next_values)
+        .fold((0, 0), |(acc0, acc1), (e0, e1)| (acc0 + e0, acc1 + e1));
+    Ok(sum)
 }
 
-fn parse_sequence(sequence: &String) -> Vec<isize> {
-    sequence.split(" ")
-        .map(|n| n.parse::<isize>().unwrap())
-        .collect::<Vec<_>>()
+pub fn parse_sequence(sequence: &Line) -> Result<Vec<isize>, ParseError> {
+    sequence.text.split(' ')
+        .map(|n| sequence.parse::<isize>(n))
+        .collect()
+}
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    fn parse(&self, input: &str) -> Option<Result<(), ParseError>> {
+        Some(parse_lines(9, input, parse_sequence).map(|_| ()))
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_sum_of_next_values(input)?.1.into())
+    }
+
+    fn part2(&self, input: &str) -> Result<Answer, SolveError> {
+        Ok(calculate_sum_of_next_values(input)?.0.into())
+    }
 }
 
 #[cfg(test)]
@@ -50,7 +68,7 @@ mod tests {
     fn it_solves_puzzle() {
         let input = read_input_file("input_day09.txt");
 
-        assert_eq!((900, 1987402313), calculate_sum_of_next_values(&input));
+        assert_eq!(Ok((900, 1987402313)), calculate_sum_of_next_values(&input));
     }
 
     #[test]
@@ -60,7 +78,7 @@ mod tests {
         1 3 6 10 15 21
         10 13 16 21 30 45"};
 
-        assert_eq!((2, 114), calculate_sum_of_next_values(input));
+        assert_eq!(Ok((2, 114)), calculate_sum_of_next_values(input));
     }
 
     #[test]
@@ -0,0 +1,351 @@
+use std::fmt;
+use std::fmt::Display;
+use std::ops::{Add, Index, IndexMut};
+
+use crate::parse_error::{parse_grid, ParseError};
+
+// x grows to the right, y grows downwards: (0, 0) is the top left cell
+#[derive(Debug, Hash, Copy, Clone, Default, Eq, PartialEq, PartialOrd, Ord)]
+pub struct Point {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Point {
+    pub fn new(x: isize, y: isize) -> Self {
+        Point { x, y }
+    }
+
+    pub fn step(&self, direction: Direction) -> Point {
+        *self + direction.offset()
+    }
+
+    pub fn neighbours(&self) -> [Point; 4] {
+        Direction::ALL.map(|direction| self.step(direction))
+    }
+
+    // the 4 orthogonal neighbours followed by the 4 diagonal ones
+    pub fn all_neighbours(&self) -> [Point; 8] {
+        let Point { x, y } = *self;
+        [
+            Point::new(x, y - 1), Point::new(x, y + 1), Point::new(x - 1, y), Point::new(x + 1, y),
+            Point::new(x - 1, y - 1), Point::new(x + 1, y - 1), Point::new(x - 1, y + 1), Point::new(x + 1, y + 1),
+        ]
+    }
+
+    pub fn manhattan_distance(&self, other: &Point) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        Point::new(x as isize, y as isize)
+    }
+}
+
+#[derive(Debug, Hash, Copy, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+    pub fn offset(&self) -> Point {
+        match self {
+            Direction::Up => Point::new(0, -1),
+            Direction::Down => Point::new(0, 1),
+            Direction::Left => Point::new(-1, 0),
+            Direction::Right => Point::new(1, 0),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn turn_right(&self) -> Direction {
+        self.turn_left().opposite()
+    }
+
+    pub fn arrow(&self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        }
+    }
+}
+
+// a rectangular grid stored row by row
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl Grid<char> {
+    // any character not listed in `cells` is reported, see `parse_grid`
+    pub fn parse(day: usize, input: &str, cells: &str) -> Result<Self, ParseError> {
+        Ok(Grid::new(parse_grid(day, input, cells)?))
+    }
+}
+
+impl<T> Grid<T> {
+    // rows are expected to have the same length
+    pub fn new(rows: Vec<Vec<T>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        assert!(rows.iter().all(|row| row.len() == width), "rows of a grid must have the same length");
+        Grid { cells: rows.into_iter().flatten().collect(), width, height }
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self where T: Clone {
+        Grid { cells: vec![value; width * height], width, height }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        0 <= point.x && point.x < self.width as isize && 0 <= point.y && point.y < self.height as isize
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(|index| &mut self.cells[index])
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        self.contains(point).then(|| point.y as usize * self.width + point.x as usize)
+    }
+
+    // row by row, from the top left cell
+    pub fn points(&self) -> impl Iterator<Item=Point> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| Point::from((x, y))))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Point> {
+        self.iter().find(|(_, cell)| predicate(cell)).map(|(point, _)| point)
+    }
+
+    // the 4 orthogonal neighbours inside the grid
+    pub fn neighbours(&self, point: Point) -> impl Iterator<Item=Point> + '_ {
+        point.neighbours().into_iter().filter(|&n| self.contains(n))
+    }
+
+    // the orthogonal and diagonal neighbours inside the grid
+    pub fn all_neighbours(&self, point: Point) -> impl Iterator<Item=Point> + '_ {
+        point.all_neighbours().into_iter().filter(|&n| self.contains(n))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item=&[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item=&T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1)).take(self.height)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=Vec<&T>> {
+        (0..self.width).map(|x| self.column(x).collect())
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid { cells: self.cells.iter().map(f).collect(), width: self.width, height: self.height }
+    }
+
+    // rows become columns
+    pub fn transpose(&self) -> Grid<T> where T: Clone {
+        Grid::new(self.columns().map(|column| column.into_iter().cloned().collect()).collect())
+    }
+
+    pub fn rotate_clockwise(&self) -> Grid<T> where T: Clone {
+        let mut rotated = self.transpose();
+        rotated.cells.chunks_mut(rotated.width.max(1)).for_each(|row| row.reverse());
+        rotated
+    }
+
+    pub fn rotate_counterclockwise(&self) -> Grid<T> where T: Clone {
+        let transposed = self.transpose();
+        Grid::new(transposed.rows().rev().map(<[T]>::to_vec).collect())
+    }
+
+    // the grid repeated infinitely in every direction
+    pub fn wrapping(&self) -> Wrapping<'_, T> {
+        Wrapping { grid: self }
+    }
+
+    // the grid surrounded by infinitely many `outside` cells
+    pub fn infinite(&self, outside: T) -> Infinite<'_, T> {
+        Infinite { grid: self, outside }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point).unwrap_or_else(|| panic!("{:?} is outside of the grid", point))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        self.get_mut(point).unwrap_or_else(|| panic!("{:?} is outside of the grid", point))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Wrapping<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<T> Wrapping<'_, T> {
+    pub fn get(&self, point: Point) -> &T {
+        let x = point.x.rem_euclid(self.grid.width as isize);
+        let y = point.y.rem_euclid(self.grid.height as isize);
+        &self.grid[Point::new(x, y)]
+    }
+}
+
+pub struct Infinite<'a, T> {
+    grid: &'a Grid<T>,
+    outside: T,
+}
+
+impl<T> Infinite<'_, T> {
+    pub fn get(&self, point: Point) -> &T {
+        self.grid.get(point).unwrap_or(&self.outside)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::grid::*;
+
+    fn grid() -> Grid<char> {
+        let input = indoc! {"
+        ab
+        cd
+        ef"};
+
+        Grid::parse(0, input, "abcdef").unwrap()
+    }
+
+    #[test]
+    fn it_reads_cells() {
+        let grid = grid();
+
+        assert_eq!((2, 3), (grid.width(), grid.height()));
+        assert_eq!(Some(&'d'), grid.get(Point::new(1, 1)));
+        assert_eq!(None, grid.get(Point::new(2, 1)));
+        assert_eq!(None, grid.get(Point::new(-1, 0)));
+        assert_eq!('e', grid[Point::new(0, 2)]);
+        assert_eq!(Some(Point::new(1, 2)), grid.position(|&c| c == 'f'));
+        assert_eq!("ab\ncd\nef", grid.to_string());
+    }
+
+    #[test]
+    fn it_finds_neighbours() {
+        let grid = grid();
+
+        assert_eq!(vec![Point::new(0, 1), Point::new(1, 0)], grid.neighbours(Point::new(0, 0)).collect::<Vec<_>>());
+        assert_eq!(5, grid.all_neighbours(Point::new(0, 1)).count());
+        assert_eq!(Point::new(1, 0), Point::new(0, 0).step(Direction::Right));
+        assert_eq!(Direction::Left, Direction::Right.opposite());
+        assert_eq!(Direction::Right, Direction::Up.turn_right());
+        assert_eq!('v', Direction::Down.arrow());
+        assert_eq!(7, Point::new(-1, 2).manhattan_distance(&Point::new(3, -1)));
+    }
+
+    #[test]
+    fn it_iterates_rows_and_columns() {
+        let grid = grid();
+
+        assert_eq!(vec![&['c', 'd'][..]], grid.rows().skip(1).take(1).collect::<Vec<_>>());
+        assert_eq!(&['e', 'f'], grid.row(2));
+        assert_eq!(vec![&'b', &'d', &'f'], grid.column(1).collect::<Vec<_>>());
+        assert_eq!(2, grid.columns().count());
+    }
+
+    #[test]
+    fn it_transposes_and_rotates() {
+        let grid = grid();
+
+        assert_eq!("ace\nbdf", grid.transpose().to_string());
+        assert_eq!("eca\nfdb", grid.rotate_clockwise().to_string());
+        assert_eq!("bdf\nace", grid.rotate_counterclockwise().to_string());
+        assert_eq!(grid, grid.rotate_clockwise().rotate_counterclockwise());
+        assert_eq!("12\n34\n56", grid.map(|&c| (c as u8 - b'a' + 1).to_string()).to_string());
+    }
+
+    #[test]
+    fn it_views_the_grid_as_infinite() {
+        let grid = grid();
+
+        assert_eq!(&'f', grid.wrapping().get(Point::new(-1, -1)));
+        assert_eq!(&'a', grid.wrapping().get(Point::new(4, 6)));
+        assert_eq!(&'.', grid.infinite('.').get(Point::new(4, 6)));
+        assert_eq!(&'c', grid.infinite('.').get(Point::new(0, 1)));
+    }
+}
@@ -1,15 +1,95 @@
-use std::fs;
+use std::fmt;
+use std::fmt::Display;
+use std::io::Read;
 use std::path::PathBuf;
 
-pub fn read_lines(input: &str) -> Vec<String> {
+// when set, inputs are read from this directory instead of the repository one
+pub const INPUTS_DIR_VARIABLE: &str = "AOC_INPUTS_DIR";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum InputSource {
+    InputsDir(String),
+    Path(PathBuf),
+    Stdin,
+    EnvDir { variable: String, filename: String },
+    Memory(String),
+}
+
+impl InputSource {
+    // the default source of a puzzle input: the directory in INPUTS_DIR_VARIABLE if set, the repository one otherwise
+    pub fn named(filename: &str) -> Self {
+        if std::env::var_os(INPUTS_DIR_VARIABLE).is_some() {
+            InputSource::EnvDir { variable: INPUTS_DIR_VARIABLE.to_string(), filename: filename.to_string() }
+        } else {
+            InputSource::InputsDir(filename.to_string())
+        }
+    }
+
+    pub fn for_day(day: usize) -> Self {
+        InputSource::named(&format!("input_day{:02}.txt", day))
+    }
+
+    // the content is normalized, see `normalize`
+    pub fn read(&self) -> Result<String, String> {
+        let raw = match self {
+            InputSource::InputsDir(filename) => read_file(&input_file_path(filename))?,
+            InputSource::Path(path) => read_file(path)?,
+            InputSource::Stdin => {
+                let mut input = String::new();
+                std::io::stdin().read_to_string(&mut input).map_err(|e| format!("Cannot read {}: {}", self, e))?;
+                input
+            }
+            InputSource::EnvDir { variable, filename } => {
+                let directory = std::env::var_os(variable)
+                    .ok_or_else(|| format!("Cannot read {}: the variable is not set", self))?;
+                read_file(&PathBuf::from(directory).join(filename))?
+            }
+            InputSource::Memory(content) => content.clone(),
+        };
+        Ok(normalize(&raw))
+    }
+}
+
+impl Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputSource::InputsDir(filename) => write!(f, "{}", input_file_path(filename).display()),
+            InputSource::Path(path) => write!(f, "{}", path.display()),
+            InputSource::Stdin => write!(f, "stdin"),
+            InputSource::EnvDir { variable, filename } => write!(f, "${}/{}", variable, filename),
+            InputSource::Memory(_) => write!(f, "in-memory input"),
+        }
+    }
+}
+
+fn read_file(path: &PathBuf) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))
+}
+
+// solvers get the same text on every platform: LF line endings and no trailing newline
+pub fn normalize(raw: &str) -> String {
+    let normalized = raw.replace("\r\n", "\n");
+    match normalized.strip_suffix('\n') {
+        Some(stripped) => stripped.to_string(),
+        None => normalized,
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn read_lines(input: &str) -> Vec<String> {
     input.lines().map(String::from).collect()
 }
 
-pub fn read_input_file(filename: &str) -> String {
-    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+pub fn input_file_path(filename: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("inputs")
-        .join(filename);
-    fs::read_to_string(&path).unwrap()
+        .join(filename)
+}
+
+// panics when the file cannot be read, for the tests
+#[cfg(test)]
+pub(crate) fn read_input_file(filename: &str) -> String {
+    InputSource::named(filename).read().unwrap()
 }
 
 #[cfg(test)]
@@ -30,7 +110,31 @@ mod tests {
         foo bar
         fizz
         bazz"};
-        let lines = read_lines(&input);
+        let lines = read_lines(input);
         assert_eq!(vec!["foo bar", "fizz", "bazz"], lines);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn it_normalizes_inputs() {
+        assert_eq!("a\nb", normalize("a\r\nb\r\n"));
+        assert_eq!("a\n\nb", normalize("a\n\nb\n"));
+        assert_eq!("a\n", normalize("a\n\n"));
+        assert_eq!(Ok("x\ny".to_string()), InputSource::Memory("x\r\ny\n".to_string()).read());
+    }
+
+    #[test]
+    fn it_reads_from_every_source() {
+        let expected = read_input_file("test.txt");
+
+        assert_eq!(Ok(expected.clone()), InputSource::InputsDir("test.txt".to_string()).read());
+        assert_eq!(Ok(expected.clone()), InputSource::Path(input_file_path("test.txt")).read());
+
+        std::env::set_var("AOC_TEST_INPUTS_DIR", input_file_path(""));
+        let from_env = InputSource::EnvDir { variable: "AOC_TEST_INPUTS_DIR".to_string(), filename: "test.txt".to_string() };
+        assert_eq!(Ok(expected), from_env.read());
+
+        let unset = InputSource::EnvDir { variable: "AOC_UNSET_INPUTS_DIR".to_string(), filename: "test.txt".to_string() };
+        assert_eq!(Err("Cannot read $AOC_UNSET_INPUTS_DIR/test.txt: the variable is not set".to_string()), unset.read());
+        assert!(InputSource::Path(PathBuf::from("missing.txt")).read().is_err());
+    }
+}
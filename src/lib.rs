@@ -0,0 +1,41 @@
+pub mod benchmark;
+pub mod grid;
+pub mod input_reader;
+pub mod nonogram;
+pub mod parse_error;
+pub(crate) mod search;
+pub mod solution;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+
+pub use day5::Almanac;
+pub use day16::Contraption;
+pub use day17::CityMap;
+pub use day20::Network;
+pub use grid::{Direction, Grid, Point};
+pub use input_reader::InputSource;
+pub use parse_error::ParseError;
+pub use solution::{solution, solve, Answer, Solution, SolveError};
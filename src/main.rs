@@ -1,28 +1,141 @@
-extern crate core;
-
-mod input_reader;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use advent_of_code_2023::benchmark::{compare, measure, Report};
+use advent_of_code_2023::day19::compare_engines;
+use advent_of_code_2023::input_reader::InputSource;
+use advent_of_code_2023::solution::{solve, SolveError};
+
+const USAGE: &str = "Usage: advent_of_code_2023 run <day> <part> [--input <path>|-]
+       advent_of_code_2023 bench [<day>...] [--runs <n>] [--output <report.json|report.csv>] [--baseline <report.json|report.csv>]
+       advent_of_code_2023 bench-workflows [--parts <n>] [--runs <n>]";
+
+// timings within 10% of the baseline are reported as unchanged
+const BASELINE_TOLERANCE: f64 = 0.1;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(answer) => println!("{}", answer),
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<String, String> {
+    match args.first().map(String::as_str) {
+        Some("run") => solve_day(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        Some("bench-workflows") => bench_workflows(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn solve_day(args: &[String]) -> Result<String, String> {
+    let day = parse_argument(args.first(), "day", 1..=25)?;
+    let part = parse_argument(args.get(1), "part", 1..=2)?;
+
+    let source = match &args[2..] {
+        [] => InputSource::for_day(day),
+        [flag, source] if flag == "--input" && source == "-" => InputSource::Stdin,
+        [flag, path] if flag == "--input" => InputSource::Path(PathBuf::from(path)),
+        _ => return Err(USAGE.to_string()),
+    };
+    let input = source.read()?;
+
+    solve(day, part, &input)
+        .ok_or_else(|| format!("No solver available for day {} part {}", day, part))?
+        .map(|answer| answer.to_string())
+        .map_err(describe)
+}
+
+fn bench(args: &[String]) -> Result<String, String> {
+    let mut days = vec![];
+    let mut runs = 1;
+    let mut output = None;
+    let mut baseline = None;
+
+    let mut remaining = args.iter();
+    while let Some(arg) = remaining.next() {
+        match arg.as_str() {
+            "--runs" => runs = parse_argument(remaining.next(), "runs", 1..=1000)?,
+            "--output" => output = Some(remaining.next().ok_or(USAGE)?),
+            "--baseline" => baseline = Some(remaining.next().ok_or(USAGE)?),
+            _ => days.push(parse_argument(Some(arg), "day", 1..=25)?),
+        }
+    }
+    if days.is_empty() {
+        days = (1..=25).collect();
+    }
+
+    let mut report = Report::default();
+    for day in days {
+        let input = InputSource::for_day(day).read()?;
+        let timing = measure(day, &input, runs).map_err(describe)?;
+        report.timings.push(timing);
+    }
+
+    let mut summary = report.to_string();
+    if let Some(path) = baseline {
+        let previous = read_report(path)?;
+        for change in compare(&previous, &report, BASELINE_TOLERANCE) {
+            summary.push_str(&format!("{}\n", change));
+        }
+    }
+    if let Some(path) = output {
+        let content = if path.ends_with(".csv") { report.to_csv() } else { report.to_json() };
+        fs::write(path, content).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+    }
+
+    Ok(summary.trim_end().to_string())
+}
+
+// rates the same random parts with the day 19 workflows, interpreted and compiled
+fn bench_workflows(args: &[String]) -> Result<String, String> {
+    let mut parts = 1_000_000;
+    let mut runs = 1;
+
+    let mut remaining = args.iter();
+    while let Some(arg) = remaining.next() {
+        match arg.as_str() {
+            "--parts" => parts = parse_argument(remaining.next(), "parts", 1..=100_000_000)?,
+            "--runs" => runs = parse_argument(remaining.next(), "runs", 1..=1000)?,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let input = InputSource::for_day(19).read()?;
+    let comparison = compare_engines(&input, parts, runs).map_err(|error| format!("Invalid input: {}", error))?;
+    Ok(format!(
+        "interpreted: {:.1} ms ({:.0} parts/s)\ncompiled: {:.1} ms ({:.0} parts/s)",
+        comparison.interpreted_ms, comparison.parts_per_second(comparison.interpreted_ms),
+        comparison.compiled_ms, comparison.parts_per_second(comparison.compiled_ms),
+    ))
+}
+
+fn read_report(path: &str) -> Result<Report, String> {
+    let content = InputSource::Path(PathBuf::from(path)).read()?;
+    if path.ends_with(".csv") {
+        Report::from_csv(&content)
+    } else {
+        Report::from_json(&content)
+    }
+}
+
+fn parse_argument(raw: Option<&String>, name: &str, allowed: std::ops::RangeInclusive<usize>) -> Result<usize, String> {
+    raw.and_then(|r| r.parse::<usize>().ok())
+        .filter(|value| allowed.contains(value))
+        .ok_or_else(|| format!("<{}> must be a number between {} and {}\n{}", name, allowed.start(), allowed.end(), USAGE))
+}
+
+fn describe(error: SolveError) -> String {
+    match error {
+        SolveError::Parse(error) => format!("Invalid input: {}", error),
+        unsolvable => format!("No solution: {}", unsolvable),
+    }
+}
@@ -0,0 +1,141 @@
+use crate::day12::Row;
+use crate::grid::{Grid, Point};
+
+// `#` is a filled cell, `.` an empty one and `?` a cell not known yet
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Outcome {
+    Unique(Grid<char>),
+    NoSolution,
+    // two of the solutions, there may be more
+    Multiple(Grid<char>, Grid<char>),
+}
+
+// the clues are the sizes of the filled groups, from left to right and from top to bottom
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+impl Nonogram {
+    pub fn new(rows: Vec<Vec<usize>>, columns: Vec<Vec<usize>>) -> Self {
+        Nonogram { rows, columns }
+    }
+
+    pub fn solve(&self) -> Outcome {
+        let mut solutions = vec![];
+        self.search(Grid::filled(self.columns.len(), self.rows.len(), '?'), &mut solutions);
+
+        match solutions.len() {
+            0 => Outcome::NoSolution,
+            1 => Outcome::Unique(solutions.pop().unwrap()),
+            _ => {
+                let second = solutions.pop().unwrap();
+                Outcome::Multiple(solutions.pop().unwrap(), second)
+            }
+        }
+    }
+
+    // stops as soon as a second solution is found
+    fn search(&self, mut grid: Grid<char>, solutions: &mut Vec<Grid<char>>) {
+        if !self.propagate(&mut grid) {
+            return;
+        }
+
+        let Some(unknown) = grid.position(|&cell| cell == '?') else {
+            solutions.push(grid);
+            return;
+        };
+
+        for guess in ['#', '.'] {
+            if solutions.len() > 1 {
+                return;
+            }
+            let mut guessed = grid.clone();
+            guessed[unknown] = guess;
+            self.search(guessed, solutions);
+        }
+    }
+
+    // fills the cells that are the same in every arrangement of their line, until nothing changes.
+    // Returns false when a line has no arrangement left.
+    fn propagate(&self, grid: &mut Grid<char>) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            let lines = self.rows.iter().enumerate().map(|(y, clue)| (row_points(grid, y), clue))
+                .chain(self.columns.iter().enumerate().map(|(x, clue)| (column_points(grid, x), clue)));
+
+            for (points, clue) in lines.collect::<Vec<_>>() {
+                let line: String = points.iter().map(|&point| grid[point]).collect();
+                let Some(forced) = Row::new(&line, clue).forced_cells() else {
+                    return false;
+                };
+                for (point, (before, after)) in points.into_iter().zip(line.chars().zip(forced.chars())) {
+                    if before != after {
+                        grid[point] = after;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+fn row_points(grid: &Grid<char>, y: usize) -> Vec<Point> {
+    (0..grid.width()).map(|x| Point::from((x, y))).collect()
+}
+
+fn column_points(grid: &Grid<char>, x: usize) -> Vec<Point> {
+    (0..grid.height()).map(|y| Point::from((x, y))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::nonogram::*;
+
+    #[test]
+    fn it_solves_by_propagation() {
+        let nonogram = Nonogram::new(
+            vec![vec![1], vec![3], vec![1, 1, 1], vec![5], vec![1, 1]],
+            vec![vec![2], vec![1, 2], vec![4], vec![1, 2], vec![2]],
+        );
+
+        let expected = indoc! {"
+        ..#..
+        .###.
+        #.#.#
+        #####
+        .#.#."};
+        assert_eq!(Outcome::Unique(Grid::parse(0, expected, "#.").unwrap()), nonogram.solve());
+    }
+
+    #[test]
+    fn it_solves_by_backtracking() {
+        // every line has several arrangements, propagation alone cannot fill any cell
+        let nonogram = Nonogram::new(
+            vec![vec![1, 1], vec![1], vec![1], vec![1]],
+            vec![vec![1, 1], vec![1], vec![1], vec![1]],
+        );
+
+        match nonogram.solve() {
+            Outcome::Multiple(first, second) => {
+                assert_ne!(first, second);
+                assert_eq!(5, first.iter().filter(|(_, &cell)| cell == '#').count());
+            }
+            outcome => panic!("expected several solutions, found {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn it_reports_puzzles_without_a_single_solution() {
+        let diagonal = Nonogram::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+        let solutions = (Grid::parse(0, "#.\n.#", "#.").unwrap(), Grid::parse(0, ".#\n#.", "#.").unwrap());
+        assert_eq!(Outcome::Multiple(solutions.0, solutions.1), diagonal.solve());
+
+        let impossible = Nonogram::new(vec![vec![2], vec![]], vec![vec![1], vec![]]);
+        assert_eq!(Outcome::NoSolution, impossible.solve());
+    }
+}
@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub day: usize,
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(day: usize, line: usize, column: usize, reason: impl Into<String>) -> Self {
+        ParseError { day, line, column, reason: reason.into() }
+    }
+
+    pub fn end_of_input(day: usize, line: usize, expected: &str) -> Self {
+        ParseError::new(day, line, 1, format!("expected {}, found the end of the input", expected))
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "day {}, line {}, column {}: {}", self.day, self.line, self.column, self.reason)
+    }
+}
+
+impl Error for ParseError {}
+
+// A line of a puzzle input, it knows where it comes from so that errors can point at it.
+// Line numbers and columns are 1-based, as shown by editors.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Line<'a> {
+    pub day: usize,
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn new(day: usize, number: usize, text: &'a str) -> Self {
+        Line { day, number, text }
+    }
+
+    // `at` must be a slice of the line text to report its column, otherwise the first column is used
+    pub fn error(&self, at: &str, reason: impl Into<String>) -> ParseError {
+        ParseError::new(self.day, self.number, self.column_of(at), reason)
+    }
+
+    pub fn column_of(&self, part: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let position = part.as_ptr() as usize;
+        if position >= start && position <= start + self.text.len() {
+            position - start + 1
+        } else {
+            1
+        }
+    }
+
+    pub fn parse<T: FromStr>(&self, value: &'a str) -> Result<T, ParseError> {
+        let trimmed = value.trim();
+        trimmed.parse::<T>()
+            .map_err(|_| self.error(trimmed, format!("expected a number, found `{}`", trimmed)))
+    }
+
+    pub fn split_once(&self, part: &'a str, separator: &str) -> Result<(&'a str, &'a str), ParseError> {
+        part.split_once(separator)
+            .ok_or_else(|| self.error(part, format!("expected `{}` in `{}`", separator, part)))
+    }
+}
+
+pub fn lines(day: usize, input: &str) -> impl Iterator<Item=Line<'_>> {
+    input.lines()
+        .enumerate()
+        .map(move |(index, text)| Line::new(day, index + 1, text))
+}
+
+pub fn parse_lines<'a, T>(day: usize, input: &'a str, parser: impl Fn(&Line<'a>) -> Result<T, ParseError>) -> Result<Vec<T>, ParseError> {
+    lines(day, input).map(|line| parser(&line)).collect()
+}
+
+// Parses a rectangular grid, any character not listed in `cells` is reported
+pub fn parse_grid(day: usize, input: &str, cells: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let grid = parse_grid_lines(&lines(day, input).collect::<Vec<_>>(), cells)?;
+    if grid.is_empty() {
+        return Err(ParseError::end_of_input(day, 1, "a grid"));
+    }
+
+    Ok(grid)
+}
+
+// the same for a block of lines within the input, which may be empty
+pub fn parse_grid_lines(lines: &[Line], cells: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    let mut grid: Vec<Vec<char>> = vec![];
+    for line in lines {
+        if let Some((index, cell)) = line.text.char_indices().find(|(_, c)| !cells.contains(*c)) {
+            return Err(line.error(&line.text[index..], format!("unknown cell `{}`", cell)));
+        }
+        let row: Vec<_> = line.text.chars().collect();
+        if let Some(width) = grid.first().map(Vec::len).filter(|&width| width != row.len()) {
+            return Err(line.error(line.text, format!("expected a row of {} cells, found {}", width, row.len())));
+        }
+        grid.push(row);
+    }
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_error::*;
+
+    #[test]
+    fn it_reports_the_column_of_a_part() {
+        let line = Line::new(5, 3, "50 98 x");
+        let error = line.parse::<usize>(&line.text[6..]).unwrap_err();
+
+        assert_eq!(ParseError::new(5, 3, 7, "expected a number, found `x`"), error);
+        assert_eq!("day 5, line 3, column 7: expected a number, found `x`", error.to_string());
+    }
+
+    #[test]
+    fn it_splits_a_line() {
+        let line = Line::new(8, 1, "AAA = (BBB, CCC)");
+
+        assert_eq!(Ok(("AAA", "(BBB, CCC)")), line.split_once(line.text, " = "));
+        assert_eq!(Err(ParseError::new(8, 1, 7, "expected `: ` in `(BBB, CCC)`")), line.split_once(&line.text[6..], ": "));
+    }
+
+    #[test]
+    fn it_parses_a_grid() {
+        assert_eq!(Ok(vec![vec!['.', '#'], vec!['#', '.']]), parse_grid(14, ".#\n#.", ".#"));
+        assert_eq!(Err(ParseError::new(14, 2, 2, "unknown cell `O`")), parse_grid(14, ".#\n#O", ".#"));
+        assert_eq!(Err(ParseError::new(14, 2, 1, "expected a row of 2 cells, found 3")), parse_grid(14, ".#\n#..", ".#"));
+        assert_eq!(Err(ParseError::end_of_input(14, 1, "a grid")), parse_grid(14, "", ".#"));
+    }
+
+    #[test]
+    fn it_numbers_lines() {
+        let numbers = lines(1, "a\nb\nc").map(|l| l.number).collect::<Vec<_>>();
+
+        assert_eq!(vec![1, 2, 3], numbers);
+    }
+}
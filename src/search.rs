@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+// the cost of the search and the states from the start to the goal, both included
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Found<S> {
+    pub cost: usize,
+    pub path: Vec<S>,
+}
+
+// every move costs 1
+pub fn bfs<S, I>(starts: impl IntoIterator<Item=S>,
+                 mut neighbours: impl FnMut(&S) -> I,
+                 is_goal: impl Fn(&S) -> bool) -> Option<Found<S>>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=S> {
+    let mut seen: HashSet<S> = HashSet::new();
+    let mut parents: HashMap<S, S> = HashMap::new();
+    let mut queue: VecDeque<(S, usize)> = VecDeque::new();
+    for start in starts {
+        if seen.insert(start.clone()) {
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((state, cost)) = queue.pop_front() {
+        if is_goal(&state) {
+            return Some(Found { cost, path: rebuild_path(&parents, state) });
+        }
+
+        for next in neighbours(&state) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), state.clone());
+                queue.push_back((next, cost + 1));
+            }
+        }
+    }
+
+    None
+}
+
+// every state reachable in at most `max_steps` moves, with its distance from the closest start
+pub fn reachable<S, I>(starts: impl IntoIterator<Item=S>,
+                       mut neighbours: impl FnMut(&S) -> I,
+                       max_steps: usize) -> HashMap<S, usize>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=S> {
+    let mut distances: HashMap<S, usize> = HashMap::new();
+    let mut queue: VecDeque<(S, usize)> = VecDeque::new();
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((state, distance)) = queue.pop_front() {
+        if distance == max_steps {
+            continue;
+        }
+
+        for next in neighbours(&state) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    distances
+}
+
+// `neighbours` gives the cost of every move
+pub fn dijkstra<S, I>(starts: impl IntoIterator<Item=S>,
+                      neighbours: impl FnMut(&S) -> I,
+                      is_goal: impl Fn(&S) -> bool) -> Option<Found<S>>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=(S, usize)> {
+    astar(starts, neighbours, |_| 0, is_goal)
+}
+
+// `heuristic` must never overestimate the cost to reach a goal, otherwise the path found may not be the cheapest
+pub fn astar<S, I>(starts: impl IntoIterator<Item=S>,
+                   mut neighbours: impl FnMut(&S) -> I,
+                   heuristic: impl Fn(&S) -> usize,
+                   is_goal: impl Fn(&S) -> bool) -> Option<Found<S>>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=(S, usize)> {
+    let mut costs: HashMap<S, usize> = HashMap::new();
+    let mut parents: HashMap<S, S> = HashMap::new();
+    let mut heap: BinaryHeap<Candidate<S>> = BinaryHeap::new();
+    for start in starts {
+        costs.insert(start.clone(), 0);
+        heap.push(Candidate { priority: heuristic(&start), cost: 0, state: start });
+    }
+
+    while let Some(Candidate { cost, state, .. }) = heap.pop() {
+        if cost > costs[&state] {
+            continue;
+        }
+
+        if is_goal(&state) {
+            return Some(Found { cost, path: rebuild_path(&parents, state) });
+        }
+
+        for (next, step_cost) in neighbours(&state) {
+            let next_cost = cost + step_cost;
+            if costs.get(&next).is_some_and(|&best| best <= next_cost) {
+                continue;
+            }
+
+            costs.insert(next.clone(), next_cost);
+            parents.insert(next.clone(), state.clone());
+            heap.push(Candidate { priority: next_cost + heuristic(&next), cost: next_cost, state: next });
+        }
+    }
+
+    None
+}
+
+// tries every simple path, the search is exponential so it is meant for small graphs
+pub fn longest_path<S, I>(start: S,
+                          mut neighbours: impl FnMut(&S) -> I,
+                          is_goal: impl Fn(&S) -> bool) -> Option<Found<S>>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=(S, usize)> {
+    let mut longest = None;
+    let mut visited = HashSet::from([start.clone()]);
+    let mut path = vec![start];
+    extend_path(&mut path, &mut visited, 0, &mut neighbours, &is_goal, &mut longest);
+    longest
+}
+
+fn extend_path<S, I>(path: &mut Vec<S>,
+                     visited: &mut HashSet<S>,
+                     cost: usize,
+                     neighbours: &mut impl FnMut(&S) -> I,
+                     is_goal: &impl Fn(&S) -> bool,
+                     longest: &mut Option<Found<S>>)
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=(S, usize)> {
+    let current = path.last().unwrap().clone();
+    if is_goal(&current) {
+        if longest.as_ref().is_none_or(|found| cost > found.cost) {
+            *longest = Some(Found { cost, path: path.clone() });
+        }
+        return;
+    }
+
+    for (next, step_cost) in neighbours(&current) {
+        if !visited.insert(next.clone()) {
+            continue;
+        }
+
+        path.push(next);
+        extend_path(path, visited, cost + step_cost, neighbours, is_goal, longest);
+        visited.remove(&path.pop().unwrap());
+    }
+}
+
+fn rebuild_path<S: Clone + Eq + Hash>(parents: &HashMap<S, S>, goal: S) -> Vec<S> {
+    let mut path = vec![goal];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+struct Candidate<S> {
+    priority: usize,
+    cost: usize,
+    state: S,
+}
+
+// the heap pops the lowest priority first
+impl<S> Ord for Candidate<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<S> PartialOrd for Candidate<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> PartialEq for Candidate<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S> Eq for Candidate<S> {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::search::*;
+
+    //  a --1-- b --1-- d
+    //  |               |
+    //  5       c --1-- e
+    //  |               |
+    //  f --------1---- g
+    fn graph() -> HashMap<char, Vec<(char, usize)>> {
+        let edges = [('a', 'b', 1), ('b', 'd', 1), ('d', 'e', 1), ('e', 'c', 1), ('e', 'g', 1), ('a', 'f', 5), ('f', 'g', 1)];
+        let mut graph: HashMap<char, Vec<(char, usize)>> = HashMap::new();
+        for (from, to, cost) in edges {
+            graph.entry(from).or_default().push((to, cost));
+            graph.entry(to).or_default().push((from, cost));
+        }
+        graph
+    }
+
+    #[test]
+    fn it_finds_the_shortest_path_in_steps() {
+        let graph = graph();
+        let found = bfs(['a'], |n| graph[n].iter().map(|&(to, _)| to).collect::<Vec<_>>(), |&n| n == 'g').unwrap();
+
+        assert_eq!(Found { cost: 2, path: vec!['a', 'f', 'g'] }, found);
+        assert_eq!(None, bfs(['a'], |n| graph[n].iter().map(|&(to, _)| to).collect::<Vec<_>>(), |&n| n == 'z'));
+    }
+
+    #[test]
+    fn it_finds_the_reachable_states() {
+        let graph = graph();
+        let distances = reachable(['a'], |n| graph[n].iter().map(|&(to, _)| to).collect::<Vec<_>>(), 2);
+
+        assert_eq!(HashMap::from([('a', 0), ('b', 1), ('f', 1), ('d', 2), ('g', 2)]), distances);
+    }
+
+    #[test]
+    fn it_finds_the_cheapest_path() {
+        let graph = graph();
+
+        let found = dijkstra(['a'], |n| graph[n].clone(), |&n| n == 'g').unwrap();
+        assert_eq!(Found { cost: 4, path: vec!['a', 'b', 'd', 'e', 'g'] }, found);
+
+        let heuristic = |n: &char| if *n == 'g' { 0 } else { 1 };
+        assert_eq!(Some(found), astar(['a'], |n| graph[n].clone(), heuristic, |&n| n == 'g'));
+    }
+
+    #[test]
+    fn it_finds_the_longest_simple_path() {
+        let graph = graph();
+        let found = longest_path('a', |n| graph[n].clone(), |&n| n == 'c').unwrap();
+
+        assert_eq!(Found { cost: 8, path: vec!['a', 'f', 'g', 'e', 'c'] }, found);
+    }
+}
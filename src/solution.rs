@@ -0,0 +1,154 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+
+use crate::parse_error::ParseError;
+use crate::{day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21, day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(t) => write!(f, "{}", t),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Number(value as i128)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(value: isize) -> Self {
+        Answer::Number(value as i128)
+    }
+}
+
+impl From<i128> for Answer {
+    fn from(value: i128) -> Self {
+        Answer::Number(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+// a well formed input can still have no answer, e.g. a map without a route
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SolveError {
+    Parse(ParseError),
+    Unsolvable { day: usize, reason: String },
+}
+
+impl SolveError {
+    pub fn unsolvable(day: usize, reason: impl Into<String>) -> Self {
+        SolveError::Unsolvable { day, reason: reason.into() }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(error: ParseError) -> Self {
+        SolveError::Parse(error)
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse(error) => write!(f, "{}", error),
+            SolveError::Unsolvable { day, reason } => write!(f, "day {}: {}", day, reason),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+pub trait Solution {
+    // none for the days whose parsing is not separated from solving
+    fn parse(&self, _input: &str) -> Option<Result<(), ParseError>> {
+        None
+    }
+
+    fn part1(&self, input: &str) -> Result<Answer, SolveError>;
+    fn part2(&self, input: &str) -> Result<Answer, SolveError>;
+}
+
+const SOLUTIONS: [&(dyn Solution + Sync); 25] = [
+    &day1::Day1, &day2::Day2, &day3::Day3, &day4::Day4, &day5::Day5,
+    &day6::Day6, &day7::Day7, &day8::Day8, &day9::Day9, &day10::Day10,
+    &day11::Day11, &day12::Day12, &day13::Day13, &day14::Day14, &day15::Day15,
+    &day16::Day16, &day17::Day17, &day18::Day18, &day19::Day19, &day20::Day20,
+    &day21::Day21, &day22::Day22, &day23::Day23, &day24::Day24, &day25::Day25,
+];
+
+pub fn solution(day: usize) -> Option<&'static (dyn Solution + Sync)> {
+    SOLUTIONS.get(day.checked_sub(1)?).copied()
+}
+
+pub fn solve(day: usize, part: usize, input: &str) -> Option<Result<Answer, SolveError>> {
+    let solution = solution(day)?;
+    match part {
+        1 => Some(solution.part1(input)),
+        2 => Some(solution.part2(input)),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::solution::*;
+
+    #[test]
+    fn it_finds_solutions_by_day() {
+        assert!(solution(0).is_none());
+        assert!(solution(1).is_some());
+        assert!(solution(25).is_some());
+        assert!(solution(26).is_none());
+    }
+
+    #[test]
+    fn it_solves_a_day_part() {
+        let input = indoc! {"
+        0 3 6 9 12 15
+        1 3 6 10 15 21
+        10 13 16 21 30 45"};
+
+        assert_eq!(Some(Ok(Answer::Number(114))), solve(9, 1, input));
+        assert_eq!(Some(Ok(Answer::Number(2))), solve(9, 2, input));
+        assert_eq!(None, solve(9, 3, input));
+    }
+
+    #[test]
+    fn it_reports_a_malformed_input() {
+        let error = solve(9, 1, "0 3 six 9").unwrap().unwrap_err();
+
+        assert_eq!("day 9, line 1, column 5: expected a number, found `six`", error.to_string());
+    }
+
+    #[test]
+    fn it_reports_an_unsolvable_input() {
+        let error = solve(17, 2, "111\n111").unwrap().unwrap_err();
+
+        assert_eq!(SolveError::unsolvable(17, "no route reaches the bottom right city block"), error);
+        assert_eq!("day 17: no route reaches the bottom right city block", error.to_string());
+    }
+
+    #[test]
+    fn it_displays_an_answer() {
+        assert_eq!("-3", Answer::from(-3isize).to_string());
+        assert_eq!("Merry Christmas!", Answer::from("Merry Christmas!").to_string());
+    }
+}